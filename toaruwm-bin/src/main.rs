@@ -20,7 +20,10 @@ use tracing_subscriber::{fmt as logger, fmt::format::FmtSpan};
 use toaruwm::bindings::{
     mb, ButtonIndex as Idx, Keybinds, Keymap, ModKey, MouseEventKind::*, Mousebinds,
 };
+use toaruwm::core::ScratchpadSpec;
+use toaruwm::layouts::update::{DecMain, IncMain, ResizeMain};
 use toaruwm::manager::config::NO_CHECKS;
+use toaruwm::manager::rules::Pattern;
 use toaruwm::types::{Cardinal::*, Direction::*};
 use toaruwm::{hook, ToaruConfig, WindowManager};
 use toaruwm::{InitX11RB, ToaruWM};
@@ -33,6 +36,7 @@ const KEYBINDS: &[(&str, fn(Wm))] = &[
     ("M-Return", |wm| wm.run_external("alacritty", &[])),
     ("M-r", |wm| wm.run_external("dmenu_run", &["-b"])),
     ("M-q", |wm| wm.close_focused_window()),
+    ("M-S-c", |wm| wm.kill_focused_window()),
     ("M-S-d", |wm| wm.dump_internal_state()),
     ("M-S-q", |wm| wm.quit()),
     ("M-k", |wm| wm.cycle_focus(Forward)),
@@ -51,6 +55,11 @@ const KEYBINDS: &[(&str, fn(Wm))] = &[
     ("M-S-2", |wm| wm.send_focused_to("2")),
     ("M-S-3", |wm| wm.send_focused_to("3")),
     ("M-Tab", |wm| wm.cycle_layout(Forward)),
+    ("M-i", |wm| wm.update_current_layout(IncMain(1))),
+    ("M-d", |wm| wm.update_current_layout(DecMain(1))),
+    ("M-h", |wm| wm.update_current_layout(ResizeMain(-0.05))),
+    ("M-l", |wm| wm.update_current_layout(ResizeMain(0.05))),
+    ("M-grave", |wm| wm.toggle_scratchpad("term")),
 ];
 
 pub fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -69,7 +78,15 @@ pub fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
         // register as global
         .try_init()?;
 
-    let config = ToaruConfig::builder().border_px(4).finish(NO_CHECKS)?;
+    let config = ToaruConfig::builder()
+        .border_px(4)
+        .scratchpads([ScratchpadSpec::new(
+            "term",
+            "alacritty",
+            ["--class", "scratchpad"],
+            Pattern::exact("scratchpad"),
+        )])
+        .finish(NO_CHECKS)?;
 
     //* 1: Setup X Connection and allocate new WM object
     let mut manager = toaruwm::x11rb_backed_wm(config)?;
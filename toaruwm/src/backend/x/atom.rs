@@ -45,6 +45,9 @@ pub enum Atom {
     /// WM_CLASS
     #[strum(serialize = "WM_CLASS")]
     WmClass,
+    /// WM_CLIENT_MACHINE
+    #[strum(serialize = "WM_CLIENT_MACHINE")]
+    WmClientMachine,
     /// WM_DELETE_WINDOW
     #[strum(serialize = "WM_DELETE_WINDOW")]
     WmDeleteWindow,
@@ -75,6 +78,9 @@ pub enum Atom {
     /// WM_TAKE_FOCUS
     #[strum(serialize = "WM_TAKE_FOCUS")]
     WmTakeFocus,
+    /// WM_WINDOW_ROLE
+    #[strum(serialize = "WM_WINDOW_ROLE")]
+    WmWindowRole,
     /// _NET_ACTIVE_WINDOW
     #[strum(serialize = "_NET_ACTIVE_WINDOW")]
     NetActiveWindow,
@@ -117,15 +123,30 @@ pub enum Atom {
     /// _NET_WM_NAME
     #[strum(serialize = "_NET_WM_NAME")]
     NetWmName,
+    /// _NET_WM_PID
+    #[strum(serialize = "_NET_WM_PID")]
+    NetWmPid,
     /// _NET_WM_STATE
     #[strum(serialize = "_NET_WM_STATE")]
     NetWmState,
+    /// _NET_WM_STATE_DEMANDS_ATTENTION
+    #[strum(serialize = "_NET_WM_STATE_DEMANDS_ATTENTION")]
+    NetWmStateDemandsAttention,
     /// _NET_WM_STATE_FULLSCREEN
     #[strum(serialize = "_NET_WM_STATE_FULLSCREEN")]
     NetWmStateFullscreen,
     /// _NET_WM_STATE_HIDDEN
     #[strum(serialize = "_NET_WM_STATE_HIDDEN")]
     NetWmStateHidden,
+    /// _NET_WM_STRUT
+    #[strum(serialize = "_NET_WM_STRUT")]
+    NetWmStrut,
+    /// _NET_WM_STRUT_PARTIAL
+    #[strum(serialize = "_NET_WM_STRUT_PARTIAL")]
+    NetWmStrutPartial,
+    /// _NET_WORKAREA
+    #[strum(serialize = "_NET_WORKAREA")]
+    NetWorkarea,
     /// _NET_WM_WINDOW_TYPE
     #[strum(serialize = "_NET_WM_WINDOW_TYPE")]
     NetWmWindowType,
@@ -220,10 +241,16 @@ pub const EWMH_SUPPORTED_ATOMS: &[Atom] = &[
     // Atom::NetSystemTrayS0,
     // Atom::NetSystemTrayOpcode,
     // Atom::NetSystemTrayOrientationHorz,
+    Atom::NetWmDesktop,
     Atom::NetWmName,
+    Atom::NetWmPid,
     Atom::NetWmState,
+    Atom::NetWmStateDemandsAttention,
     Atom::NetWmStateFullscreen,
+    Atom::NetWmStrut,
+    Atom::NetWmStrutPartial,
     Atom::NetWmWindowType,
+    Atom::NetWorkarea,
 ];
 
 /// A type that associates either an Atom or a String with
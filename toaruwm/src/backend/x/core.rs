@@ -159,6 +159,26 @@ pub struct PointerQueryReply {
     pub mask: KeyButMask,
 }
 
+/// The map state of a window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIs)]
+pub enum MapState {
+    /// The window is unmapped.
+    Unmapped,
+    /// The window is mapped, but an ancestor is unmapped.
+    Unviewable,
+    /// The window is mapped and all its ancestors are mapped.
+    Viewable,
+}
+
+/// Reply to a window attributes query.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct WindowAttributes {
+    /// Whether the window has override-redirect set.
+    pub override_redirect: bool,
+    /// The map state of the window.
+    pub map_state: MapState,
+}
+
 /// Representation of an X window with additional data (geometry).
 #[derive(Debug, Clone, Copy)]
 pub struct XWindow {
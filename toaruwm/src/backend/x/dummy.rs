@@ -1,93 +1,764 @@
-#![allow(unused_imports, dead_code)]
+#![allow(dead_code)]
+//! A dummy connection that models an X server in memory.
+//!
+//! This is used to test higher-level code such as `Workspace`,
+//! `Desktop` and `WindowManager` without needing a running
+//! X server.
 
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
 
-use crate::core::{Client, Screen};
-use crate::types::Geometry;
+use strum::IntoEnumIterator;
+use tracing::debug;
+
+use crate::bindings::{Keybind, Mousebind};
+use crate::core::Screen;
+use crate::types::{ClientAttrs, ClientConfig, Color, Geometry, Point};
 use crate::backend::x::{
-    core::{PointerQueryReply, Result, XAtom, XConn, XError, XWindow, XWindowID},
-    event::XEvent,
+    core::{
+        MapState, PointerQueryReply, Result, StackMode, WindowAttributes, WindowClass, XAtom,
+        XError, XWindow, XWindowID, Xid,
+    },
+    atom::Atom,
+    event::{ClientMessageData, ClientMessageEvent, XEvent},
+    input::KeyButMask,
+    property::Property,
+    traits::{RandR, XConn, XCore, Xkb},
 };
 
+/// The state of a single window as tracked by a `DummyConn`.
+#[derive(Debug, Clone)]
+pub struct DummyWindow {
+    /// The ID of the window.
+    pub id: XWindowID,
+    /// The parent of the window.
+    pub parent: XWindowID,
+    /// The geometry of the window.
+    pub geom: Geometry,
+    /// The width of the window border.
+    pub border_width: u32,
+    /// The number of times the border width has been configured.
+    pub border_configures: u32,
+    /// The colour of the window border, if set.
+    pub border_colour: Option<Color>,
+    /// Whether the window is mapped.
+    pub mapped: bool,
+    /// Whether the window has override-redirect set.
+    pub override_redirect: bool,
+    /// Whether the window manager has selected events on the window.
+    pub events_selected: bool,
+    /// The properties set on the window.
+    pub properties: HashMap<String, Property>,
+}
+
+impl DummyWindow {
+    fn new(id: XWindowID, parent: XWindowID, geom: Geometry, override_redirect: bool) -> Self {
+        Self {
+            id,
+            parent,
+            geom,
+            border_width: 0,
+            border_configures: 0,
+            border_colour: None,
+            mapped: false,
+            override_redirect,
+            events_selected: false,
+            properties: HashMap::new(),
+        }
+    }
+}
+
 /// A dummy connection implementing XConn but actually
 /// does not interface with the X server at all, and
 /// should mainly be used for testing.
 ///
 /// `DummyConn` contains an internal queue that takes
 /// XEvents and dequeues them when `poll_next_event` is
-/// called, as well as an internal store of windows,
-/// representing top-level windows managed by the
-/// window manager.
+/// called, as well as an in-memory model of the server:
+/// windows with their geometry, properties and mapped state,
+/// the stacking order, interned atoms, the input focus, the
+/// pointer, and the time of the latest `PropertyNotify` event.
+///
+/// Requests made through `XConn` only update this model, and
+/// never generate any events. Tests should queue the events
+/// that a real server would send with `add_event`.
+///
+/// Once the event queue is exhausted, `poll_next_event`
+/// returns a connection error, which causes
+/// `WindowManager::run` to return, while
+/// `poll_next_event_timeout` waits out its timeout and
+/// returns no event.
+#[derive(Debug)]
 pub struct DummyConn {
-    events: VecDeque<XEvent>,
+    events: RefCell<VecDeque<XEvent>>,
     root: XWindow,
-    children: HashMap<XWindowID, Client>,
+    // each with the ID of its RandR output
+    outputs: RefCell<Vec<(Xid, Geometry)>>,
+    windows: RefCell<HashMap<XWindowID, DummyWindow>>,
+    // bottom to top
+    stack: RefCell<Vec<XWindowID>>,
+    atoms: RefCell<HashMap<String, XAtom>>,
+    focused: Cell<XWindowID>,
+    pointer: Cell<Point>,
+    time: Cell<u32>,
+    pointer_grabbed: Cell<bool>,
+    keyboard_grabbed: Cell<bool>,
+    keys: RefCell<Vec<(Keybind, XWindowID)>>,
+    buttons: RefCell<Vec<(Mousebind, XWindowID)>>,
+    sent: RefCell<Vec<(XWindowID, ClientMessageEvent)>>,
+    notifies: RefCell<Vec<(XWindowID, Geometry, u32)>>,
+    next_id: Cell<u32>,
+    next_atom: Cell<u32>,
 }
 
 impl DummyConn {
-    /// Creates a new DummyConn.
+    /// Creates a new DummyConn with a single screen covering
+    /// the root window.
     pub fn new(root: XWindow) -> Self {
-        Self {
-            events: VecDeque::new(),
+        Self::with_outputs(root, vec![root.geom])
+    }
+
+    /// Creates a new DummyConn with the given screen geometries.
+    pub fn with_outputs(root: XWindow, outputs: Vec<Geometry>) -> Self {
+        let mut windows = HashMap::new();
+        windows.insert(
+            root.id,
+            DummyWindow {
+                mapped: true,
+                ..DummyWindow::new(root.id, Xid(0), root.geom, false)
+            },
+        );
+
+        let conn = Self {
+            events: RefCell::new(VecDeque::new()),
             root,
-            children: HashMap::new(),
+            outputs: RefCell::new(Self::number_outputs(outputs)),
+            windows: RefCell::new(windows),
+            stack: RefCell::new(Vec::new()),
+            atoms: RefCell::new(HashMap::new()),
+            focused: Cell::new(root.id),
+            pointer: Cell::new(Point::new(0, 0)),
+            time: Cell::new(0),
+            pointer_grabbed: Cell::new(false),
+            keyboard_grabbed: Cell::new(false),
+            keys: RefCell::new(Vec::new()),
+            buttons: RefCell::new(Vec::new()),
+            sent: RefCell::new(Vec::new()),
+            notifies: RefCell::new(Vec::new()),
+            next_id: Cell::new(root.id.val() + 1),
+            next_atom: Cell::new(1),
+        };
+
+        // like a real server, all known atoms are interned on startup
+        for atom in Atom::iter() {
+            let _ = conn.atom(atom.as_ref());
         }
+
+        conn
     }
 
     /// Adds a single event to the internal queue to be sent out by
     /// `XConn::poll_next_event`.
-    pub fn add_event(&mut self, event: XEvent) {
-        self.events.push_back(event);
+    pub fn add_event(&self, event: XEvent) {
+        self.events.borrow_mut().push_back(event);
     }
 
     /// Adds multiple events to the internal queue.
-    pub fn add_events<I>(&mut self, events: I)
+    pub fn add_events<I>(&self, events: I)
     where
         I: IntoIterator<Item = XEvent>,
     {
-        for event in events {
-            self.events.push_back(event);
-        }
+        self.events.borrow_mut().extend(events);
+    }
+
+    /// Returns the number of events still queued.
+    pub fn pending_events(&self) -> usize {
+        self.events.borrow().len()
+    }
+
+    /// Creates a new top-level window, as a client would.
+    ///
+    /// The window is unmapped and has no properties set.
+    pub fn add_window(&self, geom: Geometry) -> XWindowID {
+        self.new_window(geom, false)
     }
 
-    pub fn add_window(&mut self, window: Client) {
-        self.children.insert(window.id(), window);
+    /// Creates a new top-level window with override-redirect set.
+    pub fn add_override_redirect(&self, geom: Geometry) -> XWindowID {
+        self.new_window(geom, true)
     }
 
+    /// Queues a `MapRequest` for the given window, as a client would
+    /// when mapping its window.
+    pub fn request_map(&self, window: XWindowID) {
+        let ovrd = self.window(window).map(|w| w.override_redirect);
+        self.add_event(XEvent::MapRequest(window, ovrd.unwrap_or(false)));
+    }
+
+    /// Replaces the root window.
     pub fn replace_root(&mut self, root: XWindow) {
+        let mut windows = self.windows.borrow_mut();
+        if let Some(mut old) = windows.remove(&self.root.id) {
+            old.id = root.id;
+            old.geom = root.geom;
+            windows.insert(root.id, old);
+        }
         self.root = root
     }
+
+    /// Replaces the set of connected screens.
+    ///
+    /// The screens are given new output IDs, in order.
+    pub fn set_outputs(&self, outputs: Vec<Geometry>) {
+        self.outputs.replace(Self::number_outputs(outputs));
+    }
+
+    /// Disconnects the screen at `idx`, keeping the output IDs
+    /// of the others.
+    pub fn remove_output(&self, idx: usize) {
+        self.outputs.borrow_mut().remove(idx);
+    }
+
+    /// Returns the location of the pointer.
+    pub fn pointer(&self) -> Point {
+        self.pointer.get()
+    }
+
+    /// Returns a snapshot of the state of a window.
+    pub fn window(&self, window: XWindowID) -> Option<DummyWindow> {
+        self.windows.borrow().get(&window).cloned()
+    }
+
+    /// Checks whether a window exists.
+    pub fn exists(&self, window: XWindowID) -> bool {
+        self.windows.borrow().contains_key(&window)
+    }
+
+    /// Checks whether a window is mapped.
+    pub fn is_mapped(&self, window: XWindowID) -> bool {
+        self.window(window).map(|w| w.mapped).unwrap_or(false)
+    }
+
+    /// Returns the window that currently has the input focus.
+    pub fn focused_window(&self) -> XWindowID {
+        self.focused.get()
+    }
+
+    /// Returns the top-level windows, from bottom to top.
+    pub fn stacking_order(&self) -> Vec<XWindowID> {
+        self.stack.borrow().clone()
+    }
+
+    /// Returns all client messages sent so far.
+    pub fn sent_messages(&self) -> Vec<(XWindowID, ClientMessageEvent)> {
+        self.sent.borrow().clone()
+    }
+
+    /// Returns all synthetic ConfigureNotify events sent so far,
+    /// as the window, geometry and border width.
+    pub fn sent_configure_notifies(&self) -> Vec<(XWindowID, Geometry, u32)> {
+        self.notifies.borrow().clone()
+    }
+
+    /// Returns whether the pointer is grabbed.
+    pub fn pointer_grabbed(&self) -> bool {
+        self.pointer_grabbed.get()
+    }
+
+    /// Returns whether the keyboard is grabbed.
+    pub fn keyboard_grabbed(&self) -> bool {
+        self.keyboard_grabbed.get()
+    }
+
+    /// Returns all currently grabbed keybinds.
+    pub fn grabbed_keys(&self) -> Vec<Keybind> {
+        self.keys.borrow().iter().map(|(kb, _)| *kb).collect()
+    }
+
+    /// Returns all currently grabbed mousebinds.
+    pub fn grabbed_buttons(&self) -> Vec<Mousebind> {
+        self.buttons.borrow().iter().map(|(mb, _)| *mb).collect()
+    }
+
+    fn new_window(&self, geom: Geometry, override_redirect: bool) -> XWindowID {
+        let id = Xid(self.next_id.get());
+        self.next_id.set(id.val() + 1);
+
+        self.windows.borrow_mut().insert(
+            id,
+            DummyWindow::new(id, self.root.id, geom, override_redirect),
+        );
+        self.stack.borrow_mut().push(id);
+
+        id
+    }
+
+    fn with_window<F, T>(&self, window: XWindowID, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut DummyWindow) -> T,
+    {
+        self.windows
+            .borrow_mut()
+            .get_mut(&window)
+            .map(f)
+            .ok_or_else(|| XError::ServerError(format!("BadWindow: {}", window)))
+    }
+
+    fn number_outputs(outputs: Vec<Geometry>) -> Vec<(Xid, Geometry)> {
+        (1..).map(Xid).zip(outputs).collect()
+    }
+
+    fn remove_window(&self, window: XWindowID) -> Result<()> {
+        if self.windows.borrow_mut().remove(&window).is_none() {
+            return Err(XError::ServerError(format!("BadWindow: {}", window)));
+        }
+        self.stack.borrow_mut().retain(|w| *w != window);
+        if self.focused.get() == window {
+            self.focused.set(self.root.id);
+        }
+        Ok(())
+    }
+
+    fn restack(&self, window: XWindowID, mode: StackMode) {
+        let mut stack = self.stack.borrow_mut();
+        let Some(pos) = stack.iter().position(|w| *w == window) else {
+            return
+        };
+        let at_top = pos + 1 == stack.len();
+        stack.remove(pos);
+
+        let sibling_pos = |stack: &Vec<XWindowID>, sib: Option<XWindowID>| {
+            sib.and_then(|s| stack.iter().position(|w| *w == s))
+        };
+
+        use StackMode::*;
+        let idx = match mode {
+            Above(sib) | TopIf(sib) => sibling_pos(&stack, sib)
+                .map(|i| i + 1)
+                .unwrap_or(stack.len()),
+            Below(sib) | BottomIf(sib) => sibling_pos(&stack, sib).unwrap_or(0),
+            Opposite(_) => {
+                if at_top {
+                    0
+                } else {
+                    stack.len()
+                }
+            }
+        };
+        stack.insert(idx, window);
+    }
 }
 
-// todo
-// impl XConn for DummyConn {
-//     fn poll_next_event(&self) -> Result<Option<XEvent>> {
-//         Ok(self.events.pop_front())
-//     }
-
-//     fn get_root(&self) -> XWindow {
-//         self.root
-//     }
-
-//     fn get_geometry(&self, window: XWindowID) -> Result<Geometry> {
-//         Ok(self.children.get(&window).ok_or(
-//             XError::OtherError(format!("no such window {}", window))
-//         )?.geometry())
-//     }
-
-//     fn query_tree(&self, window: XWindowID) -> Result<Vec<XWindowID>> {
-//         Ok(self.children.values().map(|c| c.id()).collect())
-//     }
-
-//     fn query_pointer(&self, window: XWindowID) -> Result<PointerQueryReply> {
-//         todo!()
-//     }
-
-//     fn all_outputs(&self) -> Result<Vec<Screen>> {
-//         todo!()
-//     }
-
-//     fn atom(&self, atom: &str) -> Result<XAtom> {
-//         todo!()
-//     }
-// }
+impl XCore for DummyConn {
+    fn poll_next_event(&self) -> Result<Option<XEvent>> {
+        match self.events.borrow_mut().pop_front() {
+            Some(event) => {
+                if let XEvent::PropertyNotify(prop) = &event {
+                    self.time.set(prop.time);
+                }
+                Ok(Some(event))
+            }
+            None => Err(XError::Connection("dummy connection has no more events".into())),
+        }
+    }
+
+    fn poll_next_event_timeout(&self, timeout: Duration) -> Result<Option<XEvent>> {
+        if self.events.borrow().is_empty() {
+            // nothing else is coming, so just wait it out
+            std::thread::sleep(timeout);
+            return Ok(None)
+        }
+        self.poll_next_event()
+    }
+
+    fn get_root(&self) -> XWindow {
+        self.root
+    }
+
+    fn last_timestamp(&self) -> u32 {
+        self.time.get()
+    }
+
+    fn get_geometry(&self, window: XWindowID) -> Result<Geometry> {
+        self.with_window(window, |w| w.geom)
+    }
+
+    fn query_tree(&self, window: XWindowID) -> Result<Vec<XWindowID>> {
+        if !self.exists(window) {
+            return Err(XError::ServerError(format!("BadWindow: {}", window)));
+        }
+        let windows = self.windows.borrow();
+        Ok(self
+            .stack
+            .borrow()
+            .iter()
+            .filter(|id| windows.get(id).map(|w| w.parent == window).unwrap_or(false))
+            .copied()
+            .collect())
+    }
+
+    fn query_pointer(&self, window: XWindowID) -> Result<PointerQueryReply> {
+        let pt = self.pointer.get();
+        let geom = self.get_geometry(window)?;
+        let windows = self.windows.borrow();
+
+        let child = self
+            .stack
+            .borrow()
+            .iter()
+            .rev()
+            .filter_map(|id| windows.get(id))
+            .find(|w| w.mapped && w.geom.contains_point(pt))
+            .map(|w| w.id)
+            .unwrap_or(Xid(0));
+
+        Ok(PointerQueryReply {
+            same_screen: true,
+            root: self.root.id,
+            child,
+            root_x: pt.x,
+            root_y: pt.y,
+            win_x: pt.x - geom.x,
+            win_y: pt.y - geom.y,
+            mask: KeyButMask::empty(),
+        })
+    }
+
+    fn warp_pointer(&self, window: XWindowID, x: i32, y: i32) -> Result<()> {
+        let geom = self.get_geometry(window)?;
+        self.pointer.set(Point::new(geom.x + x, geom.y + y));
+        Ok(())
+    }
+
+    fn get_window_attributes(&self, window: XWindowID) -> Result<WindowAttributes> {
+        let windows = self.windows.borrow();
+        let Some(win) = windows.get(&window) else {
+            return Err(XError::ServerError(format!("BadWindow: {}", window)))
+        };
+
+        // a window is only viewable if all its ancestors are mapped
+        let mut viewable = win.mapped;
+        let mut parent = win.parent;
+        while let Some(p) = windows.get(&parent) {
+            viewable &= p.mapped;
+            parent = p.parent;
+        }
+
+        Ok(WindowAttributes {
+            override_redirect: win.override_redirect,
+            map_state: match (win.mapped, viewable) {
+                (false, _) => MapState::Unmapped,
+                (true, false) => MapState::Unviewable,
+                (true, true) => MapState::Viewable,
+            },
+        })
+    }
+
+    fn all_outputs(&self) -> Result<Vec<Screen>> {
+        let outputs = self.outputs.borrow();
+        if outputs.is_empty() {
+            return Err(XError::NoScreens);
+        }
+        Ok(outputs
+            .iter()
+            .enumerate()
+            .map(|(i, (output, geom))| {
+                Screen::new(i as i32, *geom, self.root.id, vec![]).with_output(*output)
+            })
+            .collect())
+    }
+
+    fn atom(&self, atom: &str) -> Result<XAtom> {
+        if let Some(known) = self.lookup_interned_atom(atom) {
+            return Ok(known);
+        }
+        let new = Xid(self.next_atom.get());
+        self.next_atom.set(new.val() + 1);
+        self.atoms.borrow_mut().insert(atom.into(), new);
+
+        Ok(new)
+    }
+
+    fn lookup_atom(&self, atom: XAtom) -> Result<String> {
+        self.atoms
+            .borrow()
+            .iter()
+            .find(|(_, v)| **v == atom)
+            .map(|(k, _)| k.clone())
+            .ok_or_else(|| XError::ServerError(format!("BadAtom: {}", atom)))
+    }
+
+    fn lookup_interned_atom(&self, name: &str) -> Option<XAtom> {
+        self.atoms.borrow().get(name).copied()
+    }
+
+    fn grab_keyboard(&self) -> Result<()> {
+        self.keyboard_grabbed.set(true);
+        Ok(())
+    }
+
+    fn ungrab_keyboard(&self) -> Result<()> {
+        self.keyboard_grabbed.set(false);
+        Ok(())
+    }
+
+    fn grab_key(&self, kb: Keybind, window: XWindowID) -> Result<()> {
+        self.keys.borrow_mut().push((kb, window));
+        Ok(())
+    }
+
+    fn ungrab_key(&self, kb: Keybind, window: XWindowID) -> Result<()> {
+        self.keys
+            .borrow_mut()
+            .retain(|(k, w)| !(*k == kb && *w == window));
+        Ok(())
+    }
+
+    fn grab_button(&self, mb: Mousebind, window: XWindowID, _confine: bool) -> Result<()> {
+        self.buttons.borrow_mut().push((mb, window));
+        Ok(())
+    }
+
+    fn ungrab_button(&self, mb: Mousebind, window: XWindowID) -> Result<()> {
+        self.buttons
+            .borrow_mut()
+            .retain(|(m, w)| !(*m == mb && *w == window));
+        Ok(())
+    }
+
+    fn grab_pointer(&self, _winid: XWindowID, _mask: u32) -> Result<()> {
+        self.pointer_grabbed.set(true);
+        Ok(())
+    }
+
+    fn ungrab_pointer(&self) -> Result<()> {
+        self.pointer_grabbed.set(false);
+        Ok(())
+    }
+
+    fn create_window(&self, ty: WindowClass, geom: Geometry, managed: bool) -> Result<XWindowID> {
+        let id = self.new_window(geom, !managed);
+
+        if let WindowClass::InputOutput(a, bwidth) = ty {
+            self.with_window(id, |w| w.border_width = bwidth)?;
+            self.set_property(
+                id,
+                Atom::NetWmWindowType.as_ref(),
+                Property::Atom(vec![a.as_ref().into()]),
+            )?;
+        }
+
+        Ok(id)
+    }
+
+    fn map_window(&self, window: XWindowID) -> Result<()> {
+        self.with_window(window, |w| w.mapped = true)
+    }
+
+    fn unmap_window(&self, window: XWindowID) -> Result<()> {
+        self.with_window(window, |w| w.mapped = false)
+    }
+
+    fn destroy_window(&self, window: XWindowID) -> Result<()> {
+        let atom = Atom::WmDeleteWindow.as_ref();
+        if self.win_supports(atom, window) {
+            let atomval = self.atom(atom)?;
+            let event = ClientMessageEvent {
+                window,
+                data: ClientMessageData::U32([*atomval, 0, 0, 0, 0]),
+                type_: atomval,
+            };
+            self.send_client_message(window, event)
+        } else {
+            self.remove_window(window)
+        }
+    }
+
+    fn kill_client(&self, window: XWindowID) -> Result<()> {
+        // there are no clients here, so only the window itself goes
+        self.remove_window(window)
+    }
+
+    fn send_client_message(&self, window: XWindowID, data: ClientMessageEvent) -> Result<()> {
+        if !self.exists(window) {
+            return Err(XError::ServerError(format!("BadWindow: {}", window)));
+        }
+        self.sent.borrow_mut().push((window, data));
+        Ok(())
+    }
+
+    fn send_configure_notify(
+        &self,
+        window: XWindowID,
+        geom: Geometry,
+        border_width: u32,
+    ) -> Result<()> {
+        if !self.exists(window) {
+            return Err(XError::ServerError(format!("BadWindow: {}", window)));
+        }
+        self.notifies.borrow_mut().push((window, geom, border_width));
+        Ok(())
+    }
+
+    fn set_input_focus(&self, window: XWindowID) -> Result<()> {
+        if !self.exists(window) {
+            return Err(XError::ServerError(format!("BadWindow: {}", window)));
+        }
+        self.focused.set(window);
+        Ok(())
+    }
+
+    fn set_geometry(&self, window: XWindowID, geom: Geometry) -> Result<()> {
+        self.with_window(window, |w| w.geom = geom)
+    }
+
+    fn set_property(&self, window: XWindowID, prop: &str, data: Property) -> Result<()> {
+        self.atom(prop)?;
+        self.with_window(window, |w| {
+            w.properties.insert(prop.into(), data);
+        })
+    }
+
+    fn get_property(&self, prop: &str, window: XWindowID) -> Result<Option<Property>> {
+        self.with_window(window, |w| w.properties.get(prop).cloned())
+    }
+
+    fn set_root_scr(&mut self, scr: i32) {
+        debug!("dummy connection ignoring root screen {}", scr);
+    }
+
+    fn change_window_attributes(&self, window: XWindowID, attrs: &[ClientAttrs]) -> Result<()> {
+        use ClientAttrs::*;
+
+        self.with_window(window, |w| {
+            for attr in attrs {
+                match attr {
+                    BorderColour(c) => w.border_colour = Some(*c),
+                    EnableClientEvents | RootEventMask => w.events_selected = true,
+                    DisableClientEvents => w.events_selected = false,
+                }
+            }
+        })
+    }
+
+    fn configure_window(&self, window: XWindowID, attrs: &[ClientConfig]) -> Result<()> {
+        use ClientConfig::*;
+
+        for attr in attrs {
+            match attr {
+                BorderWidth(px) => self.with_window(window, |w| {
+                    w.border_width = *px;
+                    w.border_configures += 1;
+                })?,
+                Position(geom) => self.with_window(window, |w| w.geom = *geom)?,
+                Resize { h, w } => self.with_window(window, |win| {
+                    win.geom.height = *h;
+                    win.geom.width = *w;
+                })?,
+                Move { x, y } => self.with_window(window, |w| {
+                    w.geom.x = *x;
+                    w.geom.y = *y;
+                })?,
+                StackingMode(sm) => {
+                    if !self.exists(window) {
+                        return Err(XError::ServerError(format!("BadWindow: {}", window)));
+                    }
+                    self.restack(window, *sm)
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn reparent_window(&self, window: XWindowID, parent: XWindowID) -> Result<()> {
+        if !self.exists(parent) {
+            return Err(XError::ServerError(format!("BadWindow: {}", parent)));
+        }
+        self.with_window(window, |w| w.parent = parent)?;
+
+        // only children of the root window are tracked in the stack
+        let mut stack = self.stack.borrow_mut();
+        stack.retain(|w| *w != window);
+        if parent == self.root.id {
+            stack.push(window);
+        }
+        Ok(())
+    }
+}
+
+impl RandR for DummyConn {
+    fn initialize_randr(&self) -> Result<u8> {
+        Ok(0)
+    }
+}
+
+impl Xkb for DummyConn {
+    fn initialize_xkb(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl XConn for DummyConn {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conn() -> DummyConn {
+        DummyConn::new(XWindow::with_data(Xid(1), Geometry::new(0, 0, 1080, 1920)))
+    }
+
+    #[test]
+    fn test_windows_and_stacking() {
+        let conn = conn();
+        let one = conn.add_window(Geometry::new(0, 0, 100, 100));
+        let two = conn.add_window(Geometry::new(50, 50, 100, 100));
+
+        assert_eq!(conn.query_tree(Xid(1)).unwrap(), vec![one, two]);
+        assert!(!conn.is_mapped(one));
+
+        conn.map_window(one).unwrap();
+        conn.configure_window(one, &[ClientConfig::StackingMode(StackMode::Above(None))])
+            .unwrap();
+        assert!(conn.is_mapped(one));
+        assert_eq!(conn.stacking_order(), vec![two, one]);
+
+        conn.warp_pointer(Xid(1), 75, 75).unwrap();
+        assert_eq!(conn.query_pointer(Xid(1)).unwrap().child, one);
+
+        conn.destroy_window(one).unwrap();
+        assert!(!conn.exists(one));
+        assert_eq!(conn.query_tree(Xid(1)).unwrap(), vec![two]);
+    }
+
+    #[test]
+    fn test_atoms_and_properties() {
+        let conn = conn();
+        let win = conn.add_window(Geometry::new(0, 0, 100, 100));
+
+        let atom = conn.atom("TOARU_TEST").unwrap();
+        assert_eq!(conn.lookup_atom(atom).unwrap(), "TOARU_TEST");
+        assert_eq!(conn.atom("TOARU_TEST").unwrap(), atom);
+
+        conn.set_property(
+            win,
+            Atom::WmClass.as_ref(),
+            Property::String(vec!["xterm".into(), "XTerm".into()]),
+        )
+        .unwrap();
+        conn.set_property(
+            win,
+            Atom::WmProtocols.as_ref(),
+            Property::Atom(vec![Atom::WmDeleteWindow.to_string()]),
+        )
+        .unwrap();
+
+        assert_eq!(conn.get_wm_class(win), ("xterm".into(), "XTerm".into()));
+
+        // supports WM_DELETE_WINDOW, so it gets a client message instead
+        conn.destroy_window(win).unwrap();
+        assert!(conn.exists(win));
+        assert_eq!(conn.sent_messages().len(), 1);
+    }
+}
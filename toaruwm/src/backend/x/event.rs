@@ -92,6 +92,25 @@ pub struct ConfigureRequestData {
     pub is_root: bool,
 }
 
+impl ConfigureRequestData {
+    /// Returns `geom` with the requested position and size applied.
+    ///
+    /// Any fields not specified in the request are left as-is.
+    pub fn apply_to(&self, geom: Geometry) -> Geometry {
+        Geometry {
+            x: self.x.unwrap_or(geom.x),
+            y: self.y.unwrap_or(geom.y),
+            height: self.height.map(|h| h as i32).unwrap_or(geom.height),
+            width: self.width.map(|w| w as i32).unwrap_or(geom.width),
+        }
+    }
+
+    /// Whether the request asks for the window to be moved or resized.
+    pub fn changes_geometry(&self) -> bool {
+        self.x.is_some() || self.y.is_some() || self.height.is_some() || self.width.is_some()
+    }
+}
+
 /// Data associated with a reparent event.
 #[derive(Debug, Clone, Copy)]
 pub struct ReparentEvent {
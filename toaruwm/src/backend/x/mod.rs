@@ -106,6 +106,26 @@ pub(crate) const RANDR_MIN: u32 = 4;
 pub(crate) const XKB_MAJ: u16 = 2;
 pub(crate) const XKB_MIN: u16 = 0;
 
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+/// Waits for up to `timeout` for there to be data to read on the
+/// connection `fd`, returning whether there is.
+pub(crate) fn wait_readable(fd: RawFd, timeout: Duration) -> Result<bool> {
+    use nix::errno::Errno;
+    use nix::poll::{poll, PollFd, PollFlags};
+
+    let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+    // round up, so we don't wake up just before the timeout and spin
+    let timeout = timeout.as_nanos().div_ceil(1_000_000).try_into().unwrap_or(i32::MAX);
+    match poll(&mut fds, timeout) {
+        Ok(ready) => Ok(ready > 0),
+        // let the caller decide whether to wait again
+        Err(nix::Error::Sys(Errno::EINTR)) => Ok(false),
+        Err(e) => Err(XError::Connection(format!("could not poll connection: {}", e))),
+    }
+}
+
 // various backend-agnostic conversion implementations
 
 use std::string::FromUtf8Error;
@@ -5,7 +5,7 @@ use std::fmt;
 
 use strum::{EnumIs};
 
-use crate::types::Point;
+use crate::types::{Cardinal, Geometry, Point};
 use crate::backend::x::core::{Result, XAtom, XConn, XError, XWindowID, Xid};
 
 /// X server properties.
@@ -359,11 +359,7 @@ impl TryFrom<&[u32]> for WmHints {
 ///
 /// This struct contains all the fields in the
 /// WM_SIZE_HINTS type, but ToaruWM does not honour
-/// the following flags:
-///
-/// - Aspect ratio
-/// - Gravity
-/// - Increments
+/// the window gravity.
 #[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub struct WmSizeHints {
     pub(crate) flags: WmSizeHintsFlags,
@@ -428,6 +424,65 @@ impl WmSizeHints {
     pub fn is_set(&self, flag: WmSizeHintsFlags) -> bool {
         self.flags.contains(flag)
     }
+
+    /// Constrains a height and width to the hints, returning
+    /// the new height and width.
+    ///
+    /// The size is first shrunk to fit within the aspect ratios,
+    /// then rounded down to the nearest resize increment, and
+    /// finally clamped to the minimum and maximum sizes.
+    ///
+    /// As specified by ICCCM, the minimum size is used in place
+    /// of the base size if it is not set, and vice versa.
+    pub fn constrain(&self, height: i32, width: i32) -> (i32, i32) {
+        // these are all stored as (width, height)
+        let (mut width, mut height) = (width, height);
+        let (base_w, base_h) = self.base_size.or(self.min_size).unwrap_or((0, 0));
+        let (min_w, min_h) = self.min_size.or(self.base_size).unwrap_or((0, 0));
+
+        // only an actual base size is excluded from the aspect ratio
+        let (aspect_w, aspect_h) = self.base_size.unwrap_or((0, 0));
+        let (mut aw, mut ah) = ((width - aspect_w) as i64, (height - aspect_h) as i64);
+        if aw > 0 && ah > 0 {
+            let valid = |&(num, den): &(i32, i32)| num > 0 && den > 0;
+            if let Some((num, den)) = self.min_aspect.filter(valid) {
+                // too tall, so cut down the height
+                if aw * (den as i64) < ah * (num as i64) {
+                    ah = aw * den as i64 / num as i64;
+                }
+            }
+            if let Some((num, den)) = self.max_aspect.filter(valid) {
+                // too wide, so cut down the width
+                if aw * (den as i64) > ah * (num as i64) {
+                    aw = ah * num as i64 / den as i64;
+                }
+            }
+            width = aw as i32 + aspect_w;
+            height = ah as i32 + aspect_h;
+        }
+
+        if let Some((inc_w, inc_h)) = self.resize_inc {
+            if inc_w > 0 {
+                width -= (width - base_w).rem_euclid(inc_w);
+            }
+            if inc_h > 0 {
+                height -= (height - base_h).rem_euclid(inc_h);
+            }
+        }
+
+        width = width.max(min_w);
+        height = height.max(min_h);
+        if let Some((max_w, max_h)) = self.max_size {
+            if max_w > 0 {
+                width = width.min(max_w);
+            }
+            if max_h > 0 {
+                height = height.min(max_h);
+            }
+        }
+
+        (height.max(1), width.max(1))
+    }
 }
 
 impl TryFrom<&[u32]> for WmSizeHints {
@@ -454,7 +509,7 @@ impl TryFrom<&[u32]> for WmSizeHints {
                 None
             };
 
-        let size = if flags.contains(WMSHFlags::US_SIZE) || flags.contains(WMSHFlags::P_POSITION) {
+        let size = if flags.contains(WMSHFlags::US_SIZE) || flags.contains(WMSHFlags::P_SIZE) {
             Some((from[3] as i32, from[4] as i32))
         } else {
             None
@@ -478,8 +533,14 @@ impl TryFrom<&[u32]> for WmSizeHints {
             None
         };
 
-        // might as well directly use None for now
-        let (min_aspect, max_aspect) = (None, None);
+        let (min_aspect, max_aspect) = if flags.contains(WMSHFlags::P_ASPECT) {
+            (
+                Some((from[11] as i32, from[12] as i32)),
+                Some((from[13] as i32, from[14] as i32)),
+            )
+        } else {
+            (None, None)
+        };
 
         let base_size = if flags.contains(WMSHFlags::P_BASE_SIZE) {
             Some((from[15] as i32, from[16] as i32))
@@ -507,3 +568,204 @@ impl TryFrom<&[u32]> for WmSizeHints {
         })
     }
 }
+
+/// The space a window reserves at the edges of the screen,
+/// as set in `_NET_WM_STRUT_PARTIAL` or `_NET_WM_STRUT`.
+///
+/// All values are relative to the edges of the root window.
+/// Each range gives the span along the edge that is reserved,
+/// and spans the whole edge if set from `_NET_WM_STRUT`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Strut {
+    /// The width reserved at the left edge.
+    pub left: u32,
+    /// The width reserved at the right edge.
+    pub right: u32,
+    /// The height reserved at the top edge.
+    pub top: u32,
+    /// The height reserved at the bottom edge.
+    pub bottom: u32,
+    /// The span of the left strut along the y-axis.
+    pub left_range: (u32, u32),
+    /// The span of the right strut along the y-axis.
+    pub right_range: (u32, u32),
+    /// The span of the top strut along the x-axis.
+    pub top_range: (u32, u32),
+    /// The span of the bottom strut along the x-axis.
+    pub bottom_range: (u32, u32),
+}
+
+impl Strut {
+    /// Parses a strut from the values of either `_NET_WM_STRUT_PARTIAL`
+    /// (12 values) or `_NET_WM_STRUT` (4 values).
+    ///
+    /// Returns None if there are too few values.
+    pub fn from_values(vals: &[u32]) -> Option<Self> {
+        let full = (0, u32::MAX);
+        match vals.len() {
+            n if n >= 12 => Some(Self {
+                left: vals[0],
+                right: vals[1],
+                top: vals[2],
+                bottom: vals[3],
+                left_range: (vals[4], vals[5]),
+                right_range: (vals[6], vals[7]),
+                top_range: (vals[8], vals[9]),
+                bottom_range: (vals[10], vals[11]),
+            }),
+            n if n >= 4 => Some(Self {
+                left: vals[0],
+                right: vals[1],
+                top: vals[2],
+                bottom: vals[3],
+                left_range: full,
+                right_range: full,
+                top_range: full,
+                bottom_range: full,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns how much should be trimmed off each side of `screen`
+    /// to keep clear of the strut, given the geometry of the root window.
+    ///
+    /// Sides that the strut does not reach are omitted.
+    pub fn reserved(&self, screen: Geometry, root: Geometry) -> Vec<(Cardinal, i32)> {
+        use Cardinal::*;
+
+        let overlaps = |(start, end): (u32, u32), from: i32, len: i32| {
+            (start as i64) < (from + len) as i64 && (end as i64) >= from as i64
+        };
+
+        let left = self.left as i32 - screen.x;
+        let right = (screen.x + screen.width) - (root.width - self.right as i32);
+        let top = self.top as i32 - screen.y;
+        let bottom = (screen.y + screen.height) - (root.height - self.bottom as i32);
+
+        [
+            (Left, self.left, left, overlaps(self.left_range, screen.y, screen.height)),
+            (Right, self.right, right, overlaps(self.right_range, screen.y, screen.height)),
+            (Up, self.top, top, overlaps(self.top_range, screen.x, screen.width)),
+            (Down, self.bottom, bottom, overlaps(self.bottom_range, screen.x, screen.width)),
+        ]
+        .into_iter()
+        .filter(|&(_, strut, trim, overlaps)| strut > 0 && trim > 0 && overlaps)
+        .map(|(dir, _, trim, _)| {
+            let max = match dir {
+                Left | Right => screen.width,
+                Up | Down => screen.height,
+            };
+            (dir, trim.min(max))
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use Cardinal::*;
+
+    #[test]
+    fn test_size_hints_min_max() {
+        let hints = WmSizeHints::new();
+        assert_eq!(hints.constrain(100, 200), (100, 200));
+        assert_eq!(hints.constrain(0, -5), (1, 1));
+
+        let hints = WmSizeHints {
+            min_size: Some((60, 50)),
+            max_size: Some((300, 400)),
+            ..WmSizeHints::new()
+        };
+        assert_eq!(hints.constrain(10, 1000), (50, 300));
+        assert_eq!(hints.constrain(1000, 10), (400, 60));
+    }
+
+    #[test]
+    fn test_size_hints_increments() {
+        // increments count up from the base size
+        let hints = WmSizeHints {
+            base_size: Some((10, 20)),
+            resize_inc: Some((8, 16)),
+            ..WmSizeHints::new()
+        };
+        assert_eq!(hints.constrain(90, 100), (84, 98));
+
+        // which is the minimum size if not set
+        let hints = WmSizeHints {
+            min_size: Some((12, 12)),
+            resize_inc: Some((10, 10)),
+            ..WmSizeHints::new()
+        };
+        assert_eq!(hints.constrain(55, 55), (52, 52));
+
+        // and vice versa
+        let hints = WmSizeHints {
+            base_size: Some((30, 30)),
+            ..WmSizeHints::new()
+        };
+        assert_eq!(hints.constrain(10, 10), (30, 30));
+    }
+
+    #[test]
+    fn test_size_hints_aspect() {
+        let hints = WmSizeHints {
+            min_aspect: Some((1, 1)),
+            max_aspect: Some((2, 1)),
+            ..WmSizeHints::new()
+        };
+        assert_eq!(hints.constrain(100, 150), (100, 150));
+        // too wide
+        assert_eq!(hints.constrain(100, 300), (100, 200));
+        // too tall
+        assert_eq!(hints.constrain(300, 100), (100, 100));
+
+        // the base size is left out of the ratio
+        let hints = WmSizeHints {
+            min_aspect: Some((1, 1)),
+            max_aspect: Some((1, 1)),
+            base_size: Some((10, 10)),
+            ..WmSizeHints::new()
+        };
+        assert_eq!(hints.constrain(210, 110), (110, 110));
+    }
+
+    #[test]
+    fn test_strut_from_values() {
+        let strut = Strut::from_values(&[0, 0, 30, 0]).unwrap();
+        assert_eq!(strut.top, 30);
+        assert_eq!(strut.top_range, (0, u32::MAX));
+        assert!(Strut::from_values(&[0, 0, 30]).is_none());
+    }
+
+    #[test]
+    fn test_strut_reserved() {
+        let root = Geometry::new(0, 0, 1080, 3840);
+        let (left, right) = (
+            Geometry::new(0, 0, 1080, 1920),
+            Geometry::new(1920, 0, 1080, 1920),
+        );
+
+        // a full strut reaches every screen along its edge
+        let top = Strut::from_values(&[0, 0, 30, 0]).unwrap();
+        assert_eq!(top.reserved(left, root), [(Up, 30)]);
+        assert_eq!(top.reserved(right, root), [(Up, 30)]);
+
+        // a partial strut only reaches the screens it spans
+        let top = Strut::from_values(&[0, 0, 30, 0, 0, 0, 0, 0, 0, 1919, 0, 0]).unwrap();
+        assert_eq!(top.reserved(left, root), [(Up, 30)]);
+        assert!(top.reserved(right, root).is_empty());
+
+        // struts are relative to the root window, not the screen
+        let side = Strut::from_values(&[0, 40, 0, 0]).unwrap();
+        assert!(side.reserved(left, root).is_empty());
+        assert_eq!(side.reserved(right, root), [(Right, 40)]);
+
+        // and never take more than the whole screen
+        let wide = Strut::from_values(&[2000, 0, 0, 20]).unwrap();
+        assert_eq!(wide.reserved(left, root), [(Left, 1920), (Down, 20)]);
+        assert_eq!(wide.reserved(right, root), [(Left, 80), (Down, 20)]);
+    }
+}
@@ -39,16 +39,20 @@
 //! 
 //! [1]: crate::WindowManager
 use std::str::FromStr;
+use std::time::Duration;
 
 use tracing::{debug, error, warn};
 
 use crate::bindings::{Keybind, Mousebind};
 
 use super::{
-    core::{Xid, XWindowID, Result, XAtom, XError, PointerQueryReply, XWindow, WindowClass},
+    core::{
+        Xid, XWindowID, Result, XAtom, XError, PointerQueryReply, XWindow, WindowClass,
+        WindowAttributes,
+    },
     atom::{Atom, AUTO_FLOAT_WINDOW_TYPES, UNMANAGED_WINDOW_TYPES},
     event::{ClientMessageEvent, XEvent},
-    property::{Property, WmHints, WmSizeHints, WindowState},
+    property::{Property, Strut, WmHints, WmSizeHints, WindowState},
 };
 use crate::core::Screen;
 use crate::types::{
@@ -117,9 +121,39 @@ pub trait XCore {
     /// events and process those accordingly.
     fn poll_next_event(&self) -> Result<Option<XEvent>>;
 
+    /// Like `poll_next_event`, but returns Ok(None) if no event
+    /// arrives within `timeout`.
+    ///
+    /// This lets the event loop wake up in time for timed work,
+    /// such as killing clients that were asked to close but have
+    /// not done so. By default, this ignores the timeout and calls
+    /// `poll_next_event`, so connections where that blocks should
+    /// implement this.
+    fn poll_next_event_timeout(&self, timeout: Duration) -> Result<Option<XEvent>> {
+        let _ = timeout;
+        self.poll_next_event()
+    }
+
+    /// Sends any requests that are still buffered to the X server.
+    ///
+    /// Connections that do not buffer requests do not need to
+    /// implement this.
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Returns the ID and geometry of the root window.
     fn get_root(&self) -> XWindow;
 
+    /// Returns the timestamp of the most recent event received from
+    /// the X server that carried one, for requests that need it.
+    ///
+    /// Before any such event is received, this is 0 (`CurrentTime`),
+    /// which is also what connections that do not track it return.
+    fn last_timestamp(&self) -> u32 {
+        0
+    }
+
     /// Returns the geometry of a given window.
     fn get_geometry(&self, window: XWindowID) -> Result<Geometry>;
 
@@ -129,6 +163,12 @@ pub trait XCore {
     /// Queries the X server for pointer data.
     fn query_pointer(&self, window: XWindowID) -> Result<PointerQueryReply>;
 
+    /// Moves the pointer to the given coordinates relative to `window`.
+    fn warp_pointer(&self, window: XWindowID, x: i32, y: i32) -> Result<()>;
+
+    /// Queries the X server for the attributes of a given window.
+    fn get_window_attributes(&self, window: XWindowID) -> Result<WindowAttributes>;
+
     /// Returns randr data on all connected screens.
     fn all_outputs(&self) -> Result<Vec<Screen>>;
 
@@ -193,9 +233,25 @@ pub trait XCore {
     /// via ICCCM WM_DELETE_WINDOW if supported.
     fn destroy_window(&self, window: XWindowID) -> Result<()>;
 
+    /// Forcibly closes the connection of the client that created
+    /// a window (XKillClient), destroying all of its windows.
+    fn kill_client(&self, window: XWindowID) -> Result<()>;
+
     /// Sends a message to a given client.
     fn send_client_message(&self, window: XWindowID, data: ClientMessageEvent) -> Result<()>;
 
+    /// Sends a synthetic ConfigureNotify to a given client.
+    ///
+    /// This informs the client of its current geometry when
+    /// the window manager does not honour its ConfigureRequest,
+    /// as required by ICCCM section 4.1.5.
+    fn send_configure_notify(
+        &self,
+        window: XWindowID,
+        geom: Geometry,
+        border_width: u32,
+    ) -> Result<()>;
+
     /// Sets the input focus to a given window.
     fn set_input_focus(&self, window: XWindowID) -> Result<()>;
 
@@ -466,6 +522,79 @@ pub trait XCore {
         }
     }
 
+    /// Gets EWMH's `_NET_WM_DESKTOP`.
+    ///
+    /// Returns None if not set or in case of error.
+    fn get_wm_desktop(&self, window: XWindowID) -> Option<u32> {
+        let prop = self.get_property(Atom::NetWmDesktop.as_ref(), window).ok()?;
+
+        if let Some(Property::Cardinal(idx)) = prop {
+            Some(idx)
+        } else {
+            debug!(target: "get_wm_desktop", "window {} did not set _NET_WM_DESKTOP", window);
+            None
+        }
+    }
+
+    /// Gets EWMH's `_NET_WM_PID`, the ID of the process that
+    /// owns the window.
+    ///
+    /// This is only meaningful on the machine named by
+    /// `WM_CLIENT_MACHINE`.
+    fn get_wm_pid(&self, window: XWindowID) -> Option<u32> {
+        let prop = self.get_property(Atom::NetWmPid.as_ref(), window).ok()?;
+
+        if let Some(Property::Cardinal(pid)) = prop {
+            Some(pid)
+        } else {
+            debug!(target: "get_wm_pid", "window {} did not set _NET_WM_PID", window);
+            None
+        }
+    }
+
+    /// Gets ICCCM's `WM_CLIENT_MACHINE`, the name of the machine
+    /// the client is running on.
+    fn get_wm_client_machine(&self, window: XWindowID) -> Option<String> {
+        let prop = self
+            .get_property(Atom::WmClientMachine.as_ref(), window)
+            .ok()?;
+
+        match prop {
+            Some(Property::String(mut s)) | Some(Property::UTF8String(mut s)) if !s.is_empty() => {
+                Some(s.remove(0))
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets `WM_WINDOW_ROLE`, which identifies a window among
+    /// the other windows of the same client.
+    fn get_wm_window_role(&self, window: XWindowID) -> Option<String> {
+        let prop = self
+            .get_property(Atom::WmWindowRole.as_ref(), window)
+            .ok()?;
+
+        match prop {
+            Some(Property::String(mut s)) | Some(Property::UTF8String(mut s)) if !s.is_empty() => {
+                Some(s.remove(0))
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets EWMH's `_NET_WM_STRUT_PARTIAL`, falling back
+    /// to `_NET_WM_STRUT` if it is not set.
+    ///
+    /// Returns None if neither is set or in case of error.
+    fn get_strut(&self, window: XWindowID) -> Option<Strut> {
+        [Atom::NetWmStrutPartial, Atom::NetWmStrut]
+            .into_iter()
+            .find_map(|atom| match self.get_property(atom.as_ref(), window) {
+                Ok(Some(Property::U32List(_, vals))) => Strut::from_values(&vals),
+                _ => None,
+            })
+    }
+
     /// Sets the _NET_SUPPORTED property on the root window.
     ///
     /// This indicates the protocols supported by the window manager.
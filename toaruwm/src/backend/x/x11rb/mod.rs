@@ -77,6 +77,7 @@ pub struct X11RBConn<S: ConnStatus> {
     atoms: RefCell<Atoms>, // wrap in RefCell for interior mutability
     cursor: Xid,
     mousemode: Cell<Option<ButtonIndex>>, // ditto
+    last_time: Cell<u32>, // ditto
     _marker: PhantomData<S>,
 }
 
@@ -99,6 +100,7 @@ impl X11RBConn<Uninitialized> {
             atoms,
             cursor: Xid(0),
             mousemode: Cell::new(None),
+            last_time: Cell::new(0),
             _marker: PhantomData,
         })
     }
@@ -167,6 +169,7 @@ impl X11RBConn<Uninitialized> {
             atoms: self.atoms,
             cursor,
             mousemode: self.mousemode,
+            last_time: self.last_time,
             _marker: PhantomData,
         })
     }
@@ -366,6 +369,19 @@ impl X11RBConn<Initialized> {
 
     //#[instrument(target = "x11rbconn", level = "trace", skip(self))]
     fn process_raw_event(&self, event: Event) -> Result<XEvent> {
+        // keep track of the server time, for requests that need a timestamp
+        let time = match &event {
+            Event::KeyPress(e) | Event::KeyRelease(e) => Some(e.time),
+            Event::ButtonPress(e) | Event::ButtonRelease(e) => Some(e.time),
+            Event::MotionNotify(e) => Some(e.time),
+            Event::EnterNotify(e) | Event::LeaveNotify(e) => Some(e.time),
+            Event::PropertyNotify(e) => Some(e.time),
+            _ => None,
+        };
+        if let Some(time) = time {
+            self.last_time.set(time);
+        }
+
         match event {
             //* RandR events
             // todo: use the data provided if needed
@@ -562,7 +578,14 @@ impl X11RBConn<Initialized> {
                     .map(|a| self.lookup_atom(Xid(a)).unwrap_or_else(|_| "".into()))
                     .collect()
             })),
-            "CARDINAL" => Some(Property::Cardinal(r.value32().unwrap().next().unwrap())),
+            "CARDINAL" => {
+                let vals: Vec<u32> = r.value32().ok_or(XError::ConversionError)?.collect();
+                if vals.len() == 1 {
+                    Some(Property::Cardinal(vals[0]))
+                } else {
+                    Some(Property::U32List(prop_type.clone(), vals))
+                }
+            }
             "STRING" => Some(Property::String(
                 String::from_utf8_lossy(&r.value)
                     .trim_matches('\0')
@@ -619,6 +642,7 @@ impl<S: ConnStatus + fmt::Debug> fmt::Debug for X11RBConn<S> {
             //.field("atoms", &self.atoms)
             .field("cursor", &self.cursor)
             .field("mousemode", &self.mousemode)
+            .field("last_time", &self.last_time)
             .finish()
     }
 }
@@ -1,5 +1,8 @@
 //! Implementation of the XConn trait for X11RBConn.
 //!
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
 use x11rb::connection::Connection;
 use x11rb::protocol::{
     randr::ConnectionExt as RConnectionExt,
@@ -17,13 +20,13 @@ use crate::core::Screen;
 use crate::types::{ClientAttrs, ClientConfig, Geometry};
 use crate::backend::x::{
     core::{
-        PointerQueryReply, Result, WindowClass, XAtom, XConn,
-        XCore, XError, XWindow, XWindowID, Xid,
+        MapState, PointerQueryReply, Result, WindowAttributes, WindowClass, XAtom,
+        XConn, XCore, XError, XWindow, XWindowID, Xid,
     },
     event::{ClientMessageData, ClientMessageEvent, XEvent},
     input::MODIFIERS,
     property::*,
-    Atom,
+    wait_readable, Atom,
 };
 
 use super::X11RBConn;
@@ -55,10 +58,29 @@ impl XCore for X11RBConn<Initialized> {
         Ok(Some(self.process_raw_event(event)?))
     }
 
+    fn poll_next_event_timeout(&self, timeout: Duration) -> Result<Option<XEvent>> {
+        self.conn.flush()?;
+
+        let mut event = self.conn.poll_for_event()?;
+        if event.is_none() && wait_readable(self.conn.stream().as_raw_fd(), timeout)? {
+            event = self.conn.poll_for_event()?;
+        }
+        event.map(|e| self.process_raw_event(e)).transpose()
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.conn.flush()?;
+        Ok(())
+    }
+
     fn get_root(&self) -> XWindow {
         self.root
     }
 
+    fn last_timestamp(&self) -> u32 {
+        self.last_time.get()
+    }
+
     fn get_geometry(&self, window: XWindowID) -> Result<Geometry> {
         self.get_geometry_inner(window)
     }
@@ -85,13 +107,34 @@ impl XCore for X11RBConn<Initialized> {
             root: Xid(reply.root),
             child: Xid(reply.child),
             root_x: reply.root_x as i32,
-            root_y: reply.root_x as i32,
+            root_y: reply.root_y as i32,
             win_x: reply.win_x as i32,
             win_y: reply.win_y as i32,
             mask: reply.mask.into(),
         })
     }
 
+    fn warp_pointer(&self, window: XWindowID, x: i32, y: i32) -> Result<()> {
+        trace!("Warping pointer to {}, {} on window {}", x, y, window);
+        self.conn
+            .warp_pointer(x11rb::NONE, *window, 0, 0, 0, 0, x as i16, y as i16)?
+            .check()?;
+        Ok(())
+    }
+
+    fn get_window_attributes(&self, window: XWindowID) -> Result<WindowAttributes> {
+        let reply = self.conn.get_window_attributes(*window)?.reply()?;
+
+        Ok(WindowAttributes {
+            override_redirect: reply.override_redirect,
+            map_state: match reply.map_state {
+                xproto::MapState::VIEWABLE => MapState::Viewable,
+                xproto::MapState::UNVIEWABLE => MapState::Unviewable,
+                _ => MapState::Unmapped,
+            },
+        })
+    }
+
     #[cfg_attr(
         debug_assertions,
         instrument(target = "xconn", level = "trace", skip(self))
@@ -117,7 +160,11 @@ impl XCore for X11RBConn<Initialized> {
             // construct screen
             .map(|(i, r)| {
                 let geom = Geometry::new(r.x as i32, r.y as i32, r.height as i32, r.width as i32);
-                Screen::new(i as i32, geom, Xid(info.root), vec![])
+                let scr = Screen::new(i as i32, geom, Xid(info.root), vec![]);
+                match r.outputs.first() {
+                    Some(output) => scr.with_output(Xid(*output)),
+                    None => scr,
+                }
             })
             .filter(|s| s.true_geom().width > 0)
             .collect();
@@ -424,6 +471,12 @@ impl XCore for X11RBConn<Initialized> {
         }
     }
 
+    fn kill_client(&self, window: XWindowID) -> Result<()> {
+        trace!("Killing the client of window {}", window);
+        self.conn.kill_client(*window)?.check()?;
+        Ok(())
+    }
+
     fn send_client_message(&self, window: XWindowID, data: ClientMessageEvent) -> Result<()> {
         use xproto::ClientMessageData as XClientMessageData;
         use ClientMessageData::*;
@@ -444,6 +497,34 @@ impl XCore for X11RBConn<Initialized> {
             .check()?)
     }
 
+    fn send_configure_notify(
+        &self,
+        window: XWindowID,
+        geom: Geometry,
+        border_width: u32,
+    ) -> Result<()> {
+        trace!("Sending configure notify to window {}", window);
+
+        let event = xproto::ConfigureNotifyEvent {
+            response_type: xproto::CONFIGURE_NOTIFY_EVENT,
+            sequence: 0,
+            event: *window,
+            window: *window,
+            above_sibling: x11rb::NONE,
+            x: geom.x as i16,
+            y: geom.y as i16,
+            width: geom.width as u16,
+            height: geom.height as u16,
+            border_width: border_width as u16,
+            override_redirect: false,
+        };
+
+        Ok(self
+            .conn
+            .send_event(false, *window, EventMask::STRUCTURE_NOTIFY, event)?
+            .check()?)
+    }
+
     fn set_input_focus(&self, window: XWindowID) -> Result<()> {
         trace!("Setting focus for window {}", window);
         self.conn
@@ -483,19 +564,25 @@ impl XCore for X11RBConn<Initialized> {
         let mode = xproto::PropMode::REPLACE;
         // get the name of the property
         let prop = self.atom(prop)?;
+        let is_utf8 = matches!(data, UTF8String(_));
 
         /* (type of property, format (bytes), actual data) */
-        let (ty, format, data) = match data {
+        let (ty, format, data): (u32, u8, Vec<Xid>) = match data {
             Atom(atoms) => (
-                xproto::AtomEnum::ATOM,
+                xproto::AtomEnum::ATOM.into(),
                 32,
                 atoms
                     .iter()
                     .map(|a| self.atom(a).unwrap_or(Xid(0)))
                     .collect(),
             ),
-            Cardinal(card) => (xproto::AtomEnum::CARDINAL, 32, vec![Xid(card)]),
+            Cardinal(card) => (xproto::AtomEnum::CARDINAL.into(), 32, vec![Xid(card)]),
             String(strs) | UTF8String(strs) => {
+                let ty = if is_utf8 {
+                    *self.atom("UTF8_STRING")?
+                } else {
+                    xproto::AtomEnum::STRING.into()
+                };
                 return {
                     let string = strs.join("\0");
                     self.conn
@@ -503,7 +590,7 @@ impl XCore for X11RBConn<Initialized> {
                             mode,
                             *window,
                             *prop,
-                            xproto::AtomEnum::STRING,
+                            ty,
                             8, //format
                             string.as_bytes().len() as u32,
                             string.as_bytes(),
@@ -512,7 +599,8 @@ impl XCore for X11RBConn<Initialized> {
                     Ok(())
                 };
             }
-            Window(ids) => (xproto::AtomEnum::WINDOW, 32, ids),
+            Window(ids) => (xproto::AtomEnum::WINDOW.into(), 32, ids),
+            U32List(ty, vals) => (*self.atom(&ty)?, 32, vals.into_iter().map(Xid).collect()),
             WMHints(_) | WMSizeHints(_) => {
                 return Err(XError::OtherError(
                     "Modifying WM_HINTS or WM_SIZE_HINTS is not supported".into(),
@@ -83,6 +83,7 @@ pub struct XCBConn<S: ConnStatus> {
     atoms: RefCell<Atoms>, // wrap in RefCell for interior mutability
     cursor: x::Cursor,
     mousemode: Cell<Option<ButtonIndex>>, // ditto
+    last_time: Cell<u32>, // ditto
     _marker: PhantomData<S>,
 }
 
@@ -106,6 +107,7 @@ impl XCBConn<Uninitialized> {
             atoms,
             cursor,
             mousemode: Cell::new(None),
+            last_time: Cell::new(0),
             _marker: PhantomData,
         })
     }
@@ -177,6 +179,7 @@ impl XCBConn<Uninitialized> {
             atoms: self.atoms,
             cursor,
             mousemode: self.mousemode,
+            last_time: self.last_time,
             _marker: PhantomData,
         })
     }
@@ -417,6 +420,23 @@ impl XCBConn<Initialized> {
     //#[instrument(target = "xcbconn", level = "trace", skip(self))]
     fn process_x_event(&self, event: x::Event) -> Result<XEvent> {
         use x::Event;
+
+        // keep track of the server time, for requests that need a timestamp
+        let time = match &event {
+            Event::KeyPress(e) => Some(e.time()),
+            Event::KeyRelease(e) => Some(e.time()),
+            Event::ButtonPress(e) => Some(e.time()),
+            Event::ButtonRelease(e) => Some(e.time()),
+            Event::MotionNotify(e) => Some(e.time()),
+            Event::EnterNotify(e) => Some(e.time()),
+            Event::LeaveNotify(e) => Some(e.time()),
+            Event::PropertyNotify(e) => Some(e.time()),
+            _ => None,
+        };
+        if let Some(time) = time {
+            self.last_time.set(time);
+        }
+
         match event {
             Event::ConfigureNotify(event) => Ok(XEvent::ConfigureNotify(ConfigureEvent {
                 from_root: id!(event.event()) == self.root.id,
@@ -607,7 +627,14 @@ impl XCBConn<Initialized> {
                     .map(|a| self.lookup_atom(*a).unwrap_or_else(|_| "".into()))
                     .collect::<Vec<String>>(),
             )),
-            "CARDINAL" => Some(Property::Cardinal(r.value()[0])),
+            "CARDINAL" => {
+                let vals: &[u32] = r.value();
+                if vals.len() == 1 {
+                    Some(Property::Cardinal(vals[0]))
+                } else {
+                    Some(Property::U32List(prop_type.clone(), vals.to_vec()))
+                }
+            }
             "STRING" => Some(Property::String(
                 String::from_utf8_lossy(r.value())
                     .trim_matches('\0')
@@ -656,6 +683,7 @@ impl<S: ConnStatus> fmt::Debug for XCBConn<S> {
             //.field("atoms", &self.atoms)
             .field("cursor", &self.cursor)
             .field("mousemode", &self.mousemode)
+            .field("last_time", &self.last_time)
             .finish()
     }
 }
@@ -1,5 +1,8 @@
 //! Implementation of the XConn trait for XCBConn.
 
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
 use xcb::randr;
 use xcb::x;
 use xcb::{Xid as XCBid, XidNew};
@@ -14,13 +17,13 @@ use crate::core::Screen;
 use crate::types::{ClientAttrs, ClientConfig, Geometry};
 use crate::backend::x::{
     core::{
-        PointerQueryReply, Result, WindowClass, XAtom, XConn,
-        XCore, XError, XWindow, XWindowID, Xid
+        MapState, PointerQueryReply, Result, WindowAttributes, WindowClass, XAtom,
+        XConn, XCore, XError, XWindow, XWindowID, Xid
     },
     event::{ClientMessageData, ClientMessageEvent, XEvent},
     input::MODIFIERS,
     property::*,
-    Atom,
+    wait_readable, Atom,
 };
 
 use super::XCBConn;
@@ -40,10 +43,29 @@ impl XCore for XCBConn<Initialized> {
         Ok(Some(self.process_raw_event(event)?))
     }
 
+    fn poll_next_event_timeout(&self, timeout: Duration) -> Result<Option<XEvent>> {
+        self.conn.flush()?;
+
+        let mut event = self.conn.poll_for_event()?;
+        if event.is_none() && wait_readable(self.conn.as_raw_fd(), timeout)? {
+            event = self.conn.poll_for_event()?;
+        }
+        event.map(|e| self.process_raw_event(e)).transpose()
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.conn.flush()?;
+        Ok(())
+    }
+
     fn get_root(&self) -> XWindow {
         self.root
     }
 
+    fn last_timestamp(&self) -> u32 {
+        self.last_time.get()
+    }
+
     fn get_geometry(&self, window: XWindowID) -> Result<Geometry> {
         self.get_geometry_inner(window)
     }
@@ -73,13 +95,48 @@ impl XCore for XCBConn<Initialized> {
             root: id!(reply.root()),
             child: id!(reply.child()),
             root_x: reply.root_x() as i32,
-            root_y: reply.root_x() as i32,
+            root_y: reply.root_y() as i32,
             win_x: reply.win_x() as i32,
             win_y: reply.win_y() as i32,
             mask: reply.mask().into(),
         })
     }
 
+    fn warp_pointer(&self, window: XWindowID, x: i32, y: i32) -> Result<()> {
+        trace!("Warping pointer to {}, {} on window {}", x, y, window);
+        Ok(req_and_check!(
+            self.conn,
+            &x::WarpPointer {
+                src_window: x::WINDOW_NONE,
+                dst_window: cast!(x::Window, *window),
+                src_x: 0,
+                src_y: 0,
+                src_width: 0,
+                src_height: 0,
+                dst_x: x as i16,
+                dst_y: y as i16,
+            }
+        )?)
+    }
+
+    fn get_window_attributes(&self, window: XWindowID) -> Result<WindowAttributes> {
+        let reply = req_and_reply!(
+            &self.conn,
+            &x::GetWindowAttributes {
+                window: cast!(x::Window, *window)
+            }
+        )?;
+
+        Ok(WindowAttributes {
+            override_redirect: reply.override_redirect(),
+            map_state: match reply.map_state() {
+                x::MapState::Viewable => MapState::Viewable,
+                x::MapState::Unviewable => MapState::Unviewable,
+                x::MapState::Unmapped => MapState::Unmapped,
+            },
+        })
+    }
+
     #[cfg_attr(
         debug_assertions,
         instrument(target = "xconn", level = "trace", skip(self))
@@ -126,7 +183,11 @@ impl XCore for XCBConn<Initialized> {
                     r.height() as i32,
                     r.width() as i32,
                 );
-                Screen::new(i as i32, geom, id!(info.root()), vec![])
+                let scr = Screen::new(i as i32, geom, id!(info.root()), vec![]);
+                match r.outputs().first() {
+                    Some(output) => scr.with_output(id!(output)),
+                    None => scr,
+                }
             })
             .filter(|s| s.true_geom().width > 0)
             .collect();
@@ -494,6 +555,16 @@ impl XCore for XCBConn<Initialized> {
         Ok(())
     }
 
+    fn kill_client(&self, window: XWindowID) -> Result<()> {
+        trace!("Killing the client of window {}", window);
+        Ok(req_and_check!(
+            self.conn,
+            &x::KillClient {
+                resource: *window
+            }
+        )?)
+    }
+
     fn send_client_message(&self, window: XWindowID, data: ClientMessageEvent) -> Result<()> {
         use ClientMessageData::*;
 
@@ -522,6 +593,37 @@ impl XCore for XCBConn<Initialized> {
         )?)
     }
 
+    fn send_configure_notify(
+        &self,
+        window: XWindowID,
+        geom: Geometry,
+        border_width: u32,
+    ) -> Result<()> {
+        trace!("Sending configure notify to window {}", window);
+
+        let event = x::ConfigureNotifyEvent::new(
+            cast!(x::Window, *window),
+            cast!(x::Window, *window),
+            x::WINDOW_NONE,
+            geom.x as i16,
+            geom.y as i16,
+            geom.width as u16,
+            geom.height as u16,
+            border_width as u16,
+            false,
+        );
+
+        Ok(req_and_check!(
+            self.conn,
+            &x::SendEvent {
+                propagate: false,
+                destination: x::SendEventDest::Window(cast!(x::Window, *window)),
+                event_mask: x::EventMask::STRUCTURE_NOTIFY,
+                event: &event,
+            }
+        )?)
+    }
+
     fn set_input_focus(&self, window: XWindowID) -> Result<()> {
         trace!("Setting focus for window {}", window);
         Ok(req_and_check!(
@@ -559,6 +661,7 @@ impl XCore for XCBConn<Initialized> {
 
         let mode = x::PropMode::Replace;
         let atom = self.atom(prop)?;
+        let is_utf8 = matches!(data, UTF8String(_));
 
         let (ty, data): (x::Atom, Vec<Xid>) = match data {
             Atom(atoms) => (
@@ -570,18 +673,27 @@ impl XCore for XCBConn<Initialized> {
             ),
             Cardinal(card) => (x::ATOM_CARDINAL, vec![Xid(card)]),
             String(strs) | UTF8String(strs) => {
+                let r#type = if is_utf8 {
+                    cast!(x::Atom, *self.atom("UTF8_STRING")?)
+                } else {
+                    x::ATOM_STRING
+                };
                 return Ok(req_and_check!(
                     self.conn,
                     &x::ChangeProperty {
                         mode,
                         window: cast!(x::Window, *window),
                         property: cast!(x::Atom, *atom),
-                        r#type: x::ATOM_STRING,
+                        r#type,
                         data: strs.join("\0").as_bytes()
                     }
                 )?)
             }
             Window(ids) => (x::ATOM_WINDOW, ids),
+            U32List(ty, vals) => (
+                cast!(x::Atom, *self.atom(&ty)?),
+                vals.into_iter().map(Xid).collect(),
+            ),
             WMHints(_) | WMSizeHints(_) => {
                 return Err(XError::OtherError(
                     "Modifying WM_HINTS or WM_SIZE_HINTS is not supported".into(),
@@ -11,13 +11,15 @@
 //! algorithms to resize windows.
 #![allow(dead_code)]
 
+use std::collections::HashMap;
+
 use tracing::debug;
 
 use crate::core::{Client, Workspace};
 use crate::layouts::{Layout, Layouts};
 use crate::manager::RuntimeConfig;
 use crate::types::{Cardinal, Direction, Geometry, Ring, Selector};
-use crate::backend::x::{Atom, Property, XConn, XWindowID};
+use crate::backend::x::{Atom, Property, XConn, XWindowID, Xid};
 use crate::{Result, ToaruError::*};
 
 use super::WorkspaceSpec;
@@ -33,8 +35,12 @@ pub struct Screen {
     pub(crate) true_geom: Geometry,
     /// The index of the Screen.
     pub(crate) idx: i32,
+    /// The RandR output the Screen is on, if known.
+    pub(crate) output: Option<Xid>,
     /// The set of workspaces managed under the screen.
     pub(crate) wix: Vec<String>,
+    /// The workspace currently shown on the screen.
+    pub(crate) active: Option<String>,
 }
 
 impl Screen {
@@ -45,17 +51,36 @@ impl Screen {
             effective_geom: geom,
             true_geom: geom,
             idx: screen_idx,
+            output: None,
+            active: wix.first().cloned(),
             wix,
         }
     }
+    /// Sets the RandR output the Screen is on.
+    ///
+    /// This is used to tell screens apart when they are reconfigured.
+    pub fn with_output(mut self, output: Xid) -> Self {
+        self.output = Some(output);
+        self
+    }
     /// Adds a new workspace to the Screen.
+    ///
+    /// If the Screen is not showing a workspace, it is set to show this one.
     pub fn add_workspace<S: Into<String>>(&mut self, wsname: S) {
-        self.wix.push(wsname.into());
+        let wsname = wsname.into();
+        if self.active.is_none() {
+            self.active = Some(wsname.clone());
+        }
+        self.wix.push(wsname);
     }
     /// Updates the effective area of the screen by trimming off
     /// a section in the given direction.
     pub fn update_effective(&mut self, dir: Cardinal, trim: i32) {
-        self.true_geom = self.true_geom.trim(trim, dir);
+        self.effective_geom = self.effective_geom.trim(trim, dir);
+    }
+    /// Resets the effective area of the screen to the whole screen.
+    pub fn reset_effective(&mut self) {
+        self.effective_geom = self.true_geom;
     }
     /// Returns the true geometry of the Screen.
     pub fn true_geom(&self) -> Geometry {
@@ -65,6 +90,22 @@ impl Screen {
     pub fn effective_geom(&self) -> Geometry {
         self.effective_geom
     }
+    /// Returns the index of the Screen.
+    pub fn idx(&self) -> i32 {
+        self.idx
+    }
+    /// Returns the RandR output the Screen is on, if known.
+    pub fn output(&self) -> Option<Xid> {
+        self.output
+    }
+    /// Returns the names of the workspaces managed under the Screen.
+    pub fn workspaces(&self) -> &[String] {
+        &self.wix
+    }
+    /// Returns the name of the workspace shown on the Screen, if any.
+    pub fn active_workspace(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
 }
 
 /// Encapsulates all the workspaces managed by the window manager.
@@ -80,6 +121,10 @@ pub struct Desktop {
     // * focused should never be none
     pub(crate) workspaces: Ring<Workspace>,
     last_ws: usize,
+    /// The window of each named scratchpad.
+    scratchpads: HashMap<String, XWindowID>,
+    /// Scratchpads that are hidden, outside of every workspace.
+    hidden: Vec<Client>,
 }
 
 impl Desktop {
@@ -103,6 +148,8 @@ impl Desktop {
                 workspaces
             },
             last_ws: 0,
+            scratchpads: HashMap::new(),
+            hidden: Vec::new(),
         };
 
         desktop.workspaces.set_focused(0);
@@ -119,7 +166,7 @@ impl Desktop {
 
     /// Test whether a certain window is already managed.
     pub fn is_managing(&self, id: XWindowID) -> bool {
-        self.workspaces.iter().any(|ws| ws.contains_window(id))
+        self.workspaces.iter().any(|ws| ws.contains_window(id)) || self.is_hidden(id)
     }
 
     /// Get a reference to the focused client of the focused workspace.
@@ -155,6 +202,12 @@ impl Desktop {
         &self.current().name
     }
 
+    /// Returns the index of the workspace with the given name.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.workspaces
+            .index(Selector::Condition(&|ws| ws.name == name))
+    }
+
     pub(crate) fn current_idx(&self) -> usize {
         self.workspaces.focused.expect("Focused index not set")
     }
@@ -188,7 +241,7 @@ impl Desktop {
 
     /// Get a reference to a workspace by its index
     pub fn get(&self, idx: usize) -> Option<&Workspace> {
-        if idx + 1 >= self.workspaces.len() {
+        if idx + 1 > self.workspaces.len() {
             return None;
         }
 
@@ -311,6 +364,9 @@ impl Desktop {
     }
 
     /// Send a window to a given workspace.
+    ///
+    /// The window can be on any workspace, but only the current
+    /// workspace is laid out again afterwards.
     pub fn send_window_to<X, C>(
         &mut self,
         id: XWindowID,
@@ -324,20 +380,140 @@ impl Desktop {
         C: RuntimeConfig,
     {
         debug!("Attempting to send window to workspace {}", name);
-        let Some(window) = self.current_mut().take_window(id, conn) else {
+        let Some(from) = self.workspaces.iter().position(|ws| ws.contains_window(id)) else {
             return Err(UnknownClient(id))
         };
-        debug!("Sending window {} to workspace {}", window.id(), name);
-        let Some(ws) = self.find_mut(name) else {
-            // if workspace was not found, put it back
-            self.current_mut().put_window(window);
+        if self.find(name).is_none() {
             return Err(UnknownWorkspace(name.into()))
-        };
+        }
+        let window = self.workspaces[from].take_window(id, conn).unwrap();
+        debug!("Sending window {} to workspace {}", window.id(), name);
+        let ws = self.find_mut(name).unwrap();
         ws.put_window(window);
         if ws.focused_client().is_none() {
             ws.windows.set_focused_by_winid(id);
         }
+
+        // dialogs follow their parent
+        for transient in self.workspaces[from].transients_of(id) {
+            if let Some(window) = self.workspaces[from].take_window(transient, conn) {
+                self.find_mut(name).unwrap().put_window(window);
+            }
+        }
         self.current_mut().relayout(conn, scr, cfg);
         Ok(())
     }
+
+    //* Scratchpad Methods *//
+
+    /// Returns the window of the named scratchpad, if it has one.
+    pub fn scratchpad(&self, name: &str) -> Option<XWindowID> {
+        self.scratchpads.get(name).copied()
+    }
+
+    /// Returns the names and windows of all scratchpads that have one.
+    pub fn scratchpads(&self) -> impl Iterator<Item = (&str, XWindowID)> {
+        self.scratchpads.iter().map(|(name, id)| (name.as_str(), *id))
+    }
+
+    /// Tests whether a window is a hidden scratchpad.
+    pub fn is_hidden(&self, id: XWindowID) -> bool {
+        self.hidden.iter().any(|c| c.id() == id)
+    }
+
+    /// Returns the scratchpads that are hidden.
+    pub fn hidden(&self) -> &[Client] {
+        &self.hidden
+    }
+
+    /// Makes a managed window the named scratchpad.
+    pub fn set_scratchpad(&mut self, name: &str, id: XWindowID) {
+        self.scratchpads.insert(name.into(), id);
+    }
+
+    /// Makes a new client the named scratchpad, keeping it hidden
+    /// until it is summoned.
+    pub(crate) fn add_scratchpad(&mut self, name: &str, window: Client) {
+        self.set_scratchpad(name, window.id());
+        self.hidden.push(window);
+    }
+
+    /// Shows the named scratchpad on the current workspace,
+    /// floating and centred on `scr`, and focuses it.
+    ///
+    /// The scratchpad is taken from whichever workspace it is on
+    /// if it is not hidden.
+    pub fn summon_scratchpad<X, C>(
+        &mut self,
+        name: &str,
+        conn: &X,
+        scr: &Screen,
+        cfg: &C,
+    ) -> Result<()>
+    where
+        X: XConn,
+        C: RuntimeConfig,
+    {
+        let Some(id) = self.scratchpad(name) else {
+            return Err(UnknownScratchpad(name.into()))
+        };
+        debug!("Summoning scratchpad {}", name);
+        let mut window = match self.hidden.iter().position(|c| c.id() == id) {
+            Some(idx) => self.hidden.remove(idx),
+            None => {
+                let Some(from) = self.workspaces.iter().position(|ws| ws.contains_window(id))
+                else {
+                    return Err(UnknownClient(id))
+                };
+                self.workspaces[from].take_window(id, conn).unwrap()
+            }
+        };
+        window.set_off_layout();
+        window.centre_over(scr.effective_geom());
+        window.update_geometry(conn);
+        self.current_mut().add_client(window, conn, scr, cfg);
+        Ok(())
+    }
+
+    /// Hides the named scratchpad, taking it out of its workspace.
+    ///
+    /// `scr` should be the screen showing the current workspace,
+    /// which is laid out again if the scratchpad was on it.
+    pub fn dismiss_scratchpad<X, C>(
+        &mut self,
+        name: &str,
+        conn: &X,
+        scr: &Screen,
+        cfg: &C,
+    ) -> Result<()>
+    where
+        X: XConn,
+        C: RuntimeConfig,
+    {
+        let Some(id) = self.scratchpad(name) else {
+            return Err(UnknownScratchpad(name.into()))
+        };
+        if self.is_hidden(id) {
+            return Ok(())
+        }
+        let Some(from) = self.workspaces.iter().position(|ws| ws.contains_window(id)) else {
+            return Err(UnknownClient(id))
+        };
+        debug!("Dismissing scratchpad {}", name);
+        let mut window = if from == self.current_idx() {
+            self.current_mut().del_window(id, conn, scr, cfg)?.unwrap()
+        } else {
+            self.workspaces[from].take_window(id, conn).unwrap()
+        };
+        window.unmap(conn);
+        self.hidden.push(window);
+        Ok(())
+    }
+
+    /// Forgets a window that no longer exists if it is a scratchpad,
+    /// dropping it if it was hidden.
+    pub(crate) fn forget_scratchpad(&mut self, id: XWindowID) {
+        self.hidden.retain(|c| c.id() != id);
+        self.scratchpads.retain(|_, w| *w != id);
+    }
 }
@@ -6,6 +6,7 @@
 
 pub mod desktop;
 pub mod ring;
+pub mod scratchpad;
 pub mod types;
 pub mod window;
 pub mod workspace;
@@ -15,6 +16,8 @@ pub use desktop::{Desktop, Screen};
 #[doc(inline)]
 pub use ring::{Ring, Selector};
 #[doc(inline)]
+pub use scratchpad::ScratchpadSpec;
+#[doc(inline)]
 pub use window::{Client, ClientRing};
 #[doc(inline)]
 pub use workspace::{Workspace, WorkspaceSpec};
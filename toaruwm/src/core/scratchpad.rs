@@ -0,0 +1,63 @@
+//! Types for named scratchpads.
+//!
+//! A scratchpad is a floating window, usually a terminal, that can be
+//! summoned onto the current workspace and hidden again with a single
+//! keybind. While hidden, it is kept by the [`Desktop`](super::Desktop)
+//! outside of every workspace.
+//!
+//! Each scratchpad is described by a [`ScratchpadSpec`], which gives the
+//! command that spawns its window, and the class that window is
+//! recognised by once it is mapped.
+
+use crate::manager::rules::{Pattern, WindowProps};
+
+/// A specification of a named scratchpad.
+#[derive(Debug, Clone)]
+pub struct ScratchpadSpec {
+    pub(crate) name: String,
+    pub(crate) command: String,
+    pub(crate) args: Vec<String>,
+    pub(crate) class: Pattern,
+}
+
+impl ScratchpadSpec {
+    /// Creates a new `ScratchpadSpec`.
+    ///
+    /// The window of the scratchpad is spawned by running `command`
+    /// with `args`, and is recognised by its `WM_CLASS` class
+    /// matching `class`.
+    pub fn new<N, C, A, S>(name: N, command: C, args: A, class: Pattern) -> Self
+    where
+        N: Into<String>,
+        C: Into<String>,
+        A: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            args: args.into_iter().map(|s| s.into()).collect(),
+            class,
+        }
+    }
+
+    /// The name of the scratchpad.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The command that spawns the window of the scratchpad.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// The arguments passed to the command.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Checks whether a window belongs to the scratchpad.
+    pub fn matches(&self, props: &WindowProps) -> bool {
+        self.class.matches(&props.class)
+    }
+}
@@ -19,8 +19,10 @@ use crate::core::types::{
 };
 use crate::manager::RuntimeConfig;
 use crate::backend::x::{
-    core::{XAtom, XConn, XWindow, XWindowID},
-    property::WindowState,
+    core::{StackMode, XAtom, XConn, XWindow, XWindowID},
+    event::{ClientMessageData, ClientMessageEvent},
+    property::{WindowState, WmSizeHints},
+    Atom,
 };
 
 /// A ring of Clients.
@@ -147,11 +149,20 @@ pub struct Client {
 
     initial_geom: Geometry,
     urgent: bool,
+    accepts_input: bool,
+    /* set by window rules, overriding the above and the configured border */
+    never_focus: bool,
+    border_px: Option<u32>,
+    /* the border width last set on the server, if any */
+    border_width: Option<u32>,
     fullscreen: bool,
+    /* the geometry and layout status to restore on leaving fullscreen */
+    before_fullscreen: Option<(Geometry, bool)>,
 
     /* indicates whether a client count as part of the current layout */
     inside_layout: bool,
     transient_for: Option<XWindowID>,
+    size_hints: Option<WmSizeHints>,
     mapped_state: WindowState,
     net_states: NetWindowStates,
     protocols: HashSet<XAtom>,
@@ -168,22 +179,29 @@ impl Client {
     #[cfg_attr(debug_assertions, instrument(level = "debug", skip(conn)))]
     pub fn new<X: XConn>(from: XWindowID, conn: &X) -> Self {
         let properties = conn.get_client_properties(from);
+        let initial_geom = if let Ok(geom) = conn.get_geometry(from) {
+            geom
+        } else {
+            Geometry::default()
+        };
         Self {
-            xwindow: XWindow::from(from),
+            xwindow: XWindow::with_data(from, initial_geom),
             name: properties.wm_name().into(),
             icon_name: properties.wm_icon_name().into(),
             class: {
                 let (class1, class2) = properties.wm_class();
                 (class1.into(), class2.into())
             },
-            initial_geom: if let Ok(geom) = conn.get_geometry(from) {
-                geom
-            } else {
-                Geometry::default()
-            },
+            initial_geom,
             transient_for: conn.get_wm_transient_for(from),
+            size_hints: properties.wm_size_hints().copied(),
             urgent: false,
+            accepts_input: properties.wm_hints().is_none_or(|h| h.accepts_input),
+            never_focus: false,
+            border_px: None,
+            border_width: None,
             fullscreen: false,
+            before_fullscreen: None,
             inside_layout: true,
             mapped_state: WindowState::Normal,
             net_states: NetWindowStates::new(),
@@ -260,12 +278,70 @@ impl Client {
         (&self.class.0, &self.class.1)
     }
 
+    /// Returns the window this client is transient for (WM_TRANSIENT_FOR),
+    /// such as the parent of a dialog.
+    #[inline(always)]
+    pub fn transient_for(&self) -> Option<XWindowID> {
+        self.transient_for
+    }
+
+    /// Returns the client's size hints (WM_NORMAL_HINTS), if it set any.
+    #[inline(always)]
+    pub fn size_hints(&self) -> Option<&WmSizeHints> {
+        self.size_hints.as_ref()
+    }
+
     /// Tests whether the client's urgent flag is set.
     #[inline(always)]
     pub fn is_urgent(&self) -> bool {
         self.urgent
     }
 
+    /// Returns whether the client accepts the input focus from the
+    /// window manager, as set in WM_HINTS.
+    ///
+    /// Clients that do not set the input hint are assumed to accept it.
+    #[inline(always)]
+    pub fn accepts_input(&self) -> bool {
+        self.accepts_input
+    }
+
+    /// Returns the border width the client should have, if it
+    /// differs from the configured one.
+    #[inline(always)]
+    pub fn border_px(&self) -> Option<u32> {
+        self.border_px
+    }
+
+    /// Sets the border width the client should have instead of
+    /// the configured one.
+    ///
+    /// This only changes the internal state of the client.
+    pub(crate) fn set_border_px(&mut self, border_px: Option<u32>) {
+        self.border_px = border_px;
+    }
+
+    /// Returns the border width the window was last given.
+    #[inline(always)]
+    pub fn border_width(&self) -> u32 {
+        self.border_width.unwrap_or(0)
+    }
+
+    /// Sets the border width of the window on the server,
+    /// if it does not already have that width.
+    pub fn set_border_width<X: XConn>(&mut self, conn: &X, width: u32) {
+        if self.border_width == Some(width) {
+            return
+        }
+        self.border_width = Some(width);
+        self.configure(conn, &[ClientConfig::BorderWidth(width)]);
+    }
+
+    /// Sets whether the client should never be given the input focus.
+    pub(crate) fn set_never_focus(&mut self, never_focus: bool) {
+        self.never_focus = never_focus;
+    }
+
     /// Returns whether the Client is fullscreen.
     ///
     /// Note that this is not the actual state of the client on
@@ -328,12 +404,12 @@ impl Client {
                     0
                 },
                 height: if let Some(dim) = sizes.size {
-                    dim.0
+                    dim.1
                 } else {
                     100
                 },
                 width: if let Some(dim) = sizes.size {
-                    dim.1
+                    dim.0
                 } else {
                     160
                 },
@@ -354,11 +430,13 @@ impl Client {
             self.initial_geom = initial_geom;
         }
         self.transient_for = conn.get_wm_transient_for(self.id());
+        self.size_hints = properties.wm_size_hints().copied();
         self.urgent = if let Some(hints) = properties.wm_hints() {
             hints.urgent()
         } else {
             false
         };
+        self.accepts_input = properties.wm_hints().is_none_or(|h| h.accepts_input);
         self.mapped_state = if let Some(hints) = properties.wm_hints() {
             hints.initial_state
         } else {
@@ -404,6 +482,58 @@ impl Client {
         }
     }
 
+    /// Sets the window this client is transient for.
+    ///
+    /// This only changes the internal state of the client.
+    pub(crate) fn set_transient_for(&mut self, parent: Option<XWindowID>) {
+        self.transient_for = parent;
+    }
+
+    /// Centres the client over the given geometry, without
+    /// updating it to the X server.
+    pub fn centre_over(&mut self, geom: Geometry) {
+        self.xwindow.set_pos_x(geom.x + (geom.width - self.width()) / 2);
+        self.xwindow.set_pos_y(geom.y + (geom.height - self.height()) / 2);
+    }
+
+    /// Re-reads the client's size hints from WM_NORMAL_HINTS.
+    ///
+    /// This does not change the current geometry of the client.
+    pub fn update_size_hints<X: XConn>(&mut self, conn: &X) {
+        self.size_hints = conn.get_wm_size_hints(self.id());
+    }
+
+    /// Sets or clears the client's urgent flag, updating its border
+    /// and _NET_WM_STATE_DEMANDS_ATTENTION to match.
+    ///
+    /// Clearing the flag leaves the client with an unfocused border.
+    pub fn set_urgent<X, C>(&mut self, conn: &X, cfg: &C, urgent: bool)
+    where
+        X: XConn,
+        C: RuntimeConfig,
+    {
+        self.urgent = urgent;
+        let style = if urgent {
+            BorderStyle::Urgent
+        } else {
+            BorderStyle::Unfocused
+        };
+        self.set_border(conn, cfg.border_style(style));
+
+        let Ok(atom) = conn.atom(Atom::NetWmStateDemandsAttention.as_ref()) else {
+            return
+        };
+        if urgent == self.net_states.contains(atom) {
+            return
+        }
+        if urgent {
+            self.add_wm_state(atom);
+        } else {
+            self.remove_wm_state(atom);
+        }
+        self.set_wm_states(conn);
+    }
+
     #[allow(dead_code)]
     pub(crate) fn set_initial_geom(&mut self, geom: Geometry) {
         debug!("Setting initial geom to {:#?}", geom);
@@ -454,6 +584,55 @@ impl Client {
         }
     }
 
+    /// Puts the client into fullscreen, covering `geom`.
+    ///
+    /// The client is taken off layout, loses its border, and is stacked
+    /// above everything else. Its geometry and layout status are saved
+    /// to be restored by `Client::unset_fullscreen`.
+    ///
+    /// This does not relayout the workspace the client is on.
+    pub fn set_fullscreen<X: XConn>(&mut self, conn: &X, geom: Geometry) {
+        if self.fullscreen {
+            return
+        }
+        self.before_fullscreen = Some((self.geometry(), self.inside_layout));
+        self.fullscreen = true;
+        self.inside_layout = false;
+
+        self.set_border_width(conn, 0);
+        self.configure(conn, &[ClientConfig::StackingMode(StackMode::Above(None))]);
+        self.force_geometry(conn, geom);
+
+        let atom = conn.atom(Atom::NetWmStateFullscreen.as_ref()).ok();
+        if let Some(atom) = atom.filter(|a| !self.net_states.contains(*a)) {
+            self.add_wm_state(atom);
+        }
+        self.set_wm_states(conn);
+    }
+
+    /// Takes the client out of fullscreen, restoring its border
+    /// and the geometry and layout status it had before.
+    ///
+    /// If the client returns to the layout, it is up to the caller
+    /// to relayout its workspace.
+    pub fn unset_fullscreen<X: XConn>(&mut self, conn: &X, border_px: u32) {
+        if !self.fullscreen {
+            return
+        }
+        self.fullscreen = false;
+
+        self.set_border_width(conn, self.border_px.unwrap_or(border_px));
+        if let Some((geom, inside_layout)) = self.before_fullscreen.take() {
+            self.inside_layout = inside_layout;
+            self.set_and_update_geometry(conn, geom);
+        }
+
+        if let Ok(atom) = conn.atom(Atom::NetWmStateFullscreen.as_ref()) {
+            self.remove_wm_state(atom);
+        }
+        self.set_wm_states(conn);
+    }
+
     /// Configure the `Client` using a provided connection.
     ///
     /// Use `Client::set_geometry` and `Client::update_geometry`
@@ -481,10 +660,12 @@ impl Client {
 
     /// Resize the window using _changes_ in height and width.
     ///
-    /// Does not do bounds checking.
+    /// The new size is constrained by the client's size hints,
+    /// so the actual change may differ from the one requested.
     pub fn do_resize<X: XConn>(&mut self, conn: &X, dx: i32, dy: i32) {
-        self.xwindow.update_height(dy);
-        self.xwindow.update_width(dx);
+        let (height, width) = self.constrain(self.height() + dy, self.width() + dx);
+        self.xwindow.set_height(height);
+        self.xwindow.set_width(width);
 
         conn.configure_window(
             self.xwindow.id,
@@ -583,11 +764,30 @@ impl Client {
     }
 
     /// Updates and sets the Client geometry with a given Geometry.
+    ///
+    /// The size of the geometry is constrained by the client's
+    /// size hints, keeping its position.
     pub fn set_and_update_geometry<X: XConn>(&mut self, conn: &X, geom: Geometry) {
+        let (height, width) = self.constrain(geom.height, geom.width);
+        self.set_geometry(Geometry { height, width, ..geom });
+        self.update_geometry(conn);
+    }
+
+    /// Updates and sets the Client geometry with a given Geometry,
+    /// ignoring the client's size hints.
+    pub fn force_geometry<X: XConn>(&mut self, conn: &X, geom: Geometry) {
         self.set_geometry(geom);
         self.update_geometry(conn);
     }
 
+    /// Constrains a height and width to the client's size hints.
+    fn constrain(&self, height: i32, width: i32) -> (i32, i32) {
+        match &self.size_hints {
+            Some(hints) => hints.constrain(height, width),
+            None => (height, width),
+        }
+    }
+
     /// Sets the supported protocols for the client.
     pub fn set_supported<X: XConn>(&mut self, conn: &X) {
         trace!("setting supported protocols for window {}", self.xwindow.id);
@@ -602,6 +802,69 @@ impl Client {
     pub fn supports(&self, prtcl: XAtom) -> bool {
         self.protocols.contains(&prtcl)
     }
+
+    /// Tests whether the client can be given the input focus at all.
+    ///
+    /// This is false for clients following the No Input focus model
+    /// of ICCCM, which neither accept input nor support WM_TAKE_FOCUS,
+    /// and for clients that a window rule keeps from being focused.
+    pub fn can_focus<X: XConn>(&self, conn: &X) -> bool {
+        !self.never_focus && (self.accepts_input || self.takes_focus(conn))
+    }
+
+    /// Gives the client the input focus, following its ICCCM focus model.
+    ///
+    /// Clients that accept input are focused directly, and clients that
+    /// support WM_TAKE_FOCUS are sent it, with the timestamp of the
+    /// latest event, so they can decide where the focus goes themselves.
+    /// Clients that can't be focused are left alone.
+    pub fn take_focus<X: XConn>(&self, conn: &X) {
+        if self.accepts_input {
+            conn.set_input_focus(self.id())
+                .unwrap_or_else(|e| warn!("{}", e));
+        }
+        if self.takes_focus(conn) {
+            self.send_protocol(conn, Atom::WmTakeFocus);
+        }
+    }
+
+    /// Asks the client to close itself with WM_DELETE_WINDOW.
+    ///
+    /// Does nothing and returns false if the client does not
+    /// support it.
+    pub fn request_close<X: XConn>(&self, conn: &X) -> bool {
+        let supported = conn
+            .atom(Atom::WmDeleteWindow.as_ref())
+            .is_ok_and(|atom| self.supports(atom));
+        if supported {
+            self.send_protocol(conn, Atom::WmDeleteWindow);
+        }
+        supported
+    }
+
+    /// Sends a WM_PROTOCOLS client message for `protocol`, with
+    /// the timestamp of the latest event.
+    fn send_protocol<X: XConn>(&self, conn: &X, protocol: Atom) {
+        let (Ok(protocols), Ok(atom)) = (
+            conn.atom(Atom::WmProtocols.as_ref()),
+            conn.atom(protocol.as_ref()),
+        ) else {
+            return
+        };
+        let event = ClientMessageEvent {
+            window: self.id(),
+            data: ClientMessageData::U32([*atom, conn.last_timestamp(), 0, 0, 0]),
+            type_: protocols,
+        };
+        conn.send_client_message(self.id(), event)
+            .unwrap_or_else(|e| warn!("Could not send {} to {}: {}", protocol, self.id(), e));
+    }
+
+    /// Tests whether the client supports WM_TAKE_FOCUS.
+    fn takes_focus<X: XConn>(&self, conn: &X) -> bool {
+        conn.atom(Atom::WmTakeFocus.as_ref())
+            .is_ok_and(|atom| self.supports(atom))
+    }
 }
 
 /// Maintains the focusing order of the windows of screen.
@@ -279,6 +279,13 @@ impl Workspace {
         self.windows.iter_mut().filter(|w| w.is_off_layout())
     }
 
+    /// Returns the number of clients in the workspace marked as urgent.
+    ///
+    /// Useful for showing which workspaces need attention in a bar.
+    pub fn urgent_count(&self) -> usize {
+        self.windows.iter().filter(|w| w.is_urgent()).count()
+    }
+
     /// Tests whether the workspace is empty.`
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
@@ -314,6 +321,15 @@ impl Workspace {
         layout.name()
     }
 
+    /// Returns the name of the workspace's current layout, as it
+    /// should be shown in a status bar.
+    pub fn layout_display_name(&self) -> String {
+        self.layouts
+            .focused()
+            .expect("layout focus should not be None")
+            .display_name(self)
+    }
+
     /// Returns the style of the workspace's current layout.
     #[inline]
     pub fn layout_style(&self) -> LayoutType {
@@ -392,7 +408,14 @@ impl Workspace {
         C: RuntimeConfig,
     {
         let layouts = self.layouts.gen_layout(conn, self, scr, cfg);
-        self.apply_layout(conn, layouts);
+        self.apply_layout(conn, cfg, layouts);
+
+        // keep fullscreen windows covering the screen, in case it has changed
+        for win in self.windows.iter_mut().filter(|c| c.is_fullscreen()) {
+            if win.geometry() != scr.true_geom() {
+                win.force_geometry(conn, scr.true_geom());
+            }
+        }
     }
 
     /// Adds a window to the workspace in the layout.
@@ -418,6 +441,15 @@ impl Workspace {
         self._add_window(conn, scr, cfg, Client::outside_layout(window, conn))
     }
 
+    /// Adds an already constructed client to the workspace.
+    pub(crate) fn add_client<X, C>(&mut self, window: Client, conn: &X, scr: &Screen, cfg: &C)
+    where
+        X: XConn,
+        C: RuntimeConfig,
+    {
+        self._add_window(conn, scr, cfg, window)
+    }
+
     /// Deletes the window from the workspaces and returns it.
     #[cfg_attr(
         debug_assertions,
@@ -456,10 +488,14 @@ impl Workspace {
         X: XConn,
         C: RuntimeConfig,
     {
-        let Some(_) = self.windows.get_idx(window) else {
+        let Some(client) = self.windows.lookup(window) else {
             warn!("focus_window: no window {} found in workspace", window);
             return
         };
+        if !client.can_focus(conn) {
+            debug!("window {} does not take input focus", window);
+            return
+        }
 
         debug!("found window {}", window);
         // unfocus the current focused window
@@ -483,12 +519,16 @@ impl Workspace {
         /* all we do is change border color, focus_window
         handles everything else */
         // remove focus if window to unfocus is currently focused
-        if self.windows.lookup(window).is_some() {
+        if let Some(win) = self.windows.lookup(window) {
+            // urgent windows keep their urgent border
+            let style = if win.is_urgent() {
+                BorderStyle::Urgent
+            } else {
+                BorderStyle::Unfocused
+            };
             conn.change_window_attributes(
                 window,
-                &[ClientAttrs::BorderColour(
-                    cfg.border_style(BorderStyle::Unfocused),
-                )],
+                &[ClientAttrs::BorderColour(cfg.border_style(style))],
             )
             .unwrap_or_else(|e| error!("{}", e));
         } else {
@@ -503,17 +543,29 @@ impl Workspace {
         C: RuntimeConfig,
     {
         // get the currently focused window's ID
-        if self.windows.focused_mut().is_none() {
+        let Some(prev) = self.windows.focused_idx() else {
             error!("cycle_focus for ws {}: nothing focused", self.name);
             return;
         };
-        // unfocus the window
-        self.unfocus_window(self.focused_client().unwrap().id(), conn, cfg);
-
-        //internally, cycle focus
-        self.windows.cycle_focus(dir);
+        let prev_id = self.focused_client().unwrap().id();
+
+        //internally, cycle focus, skipping windows that can't take it
+        let mut found = false;
+        for _ in 0..self.windows.len() {
+            self.windows.cycle_focus(dir);
+            if self.focused_client().unwrap().can_focus(conn) {
+                found = true;
+                break
+            }
+        }
+        if !found {
+            debug!("cycle_focus for ws {}: no window takes input focus", self.name);
+            self.windows.set_focused(prev);
+            return
+        }
 
-        // focus window
+        // unfocus the previous window and focus the new one
+        self.unfocus_window(prev_id, conn, cfg);
         self.stack_and_focus_window(conn, cfg, self.focused_client().unwrap().id());
     }
 
@@ -537,17 +589,48 @@ impl Workspace {
     }
 
     /// Toggles fullscreen on the currently focused window.
-    #[allow(unused_variables)]
     pub fn toggle_focused_fullscreen<X, C>(&mut self, conn: &X, scr: &Screen, cfg: &C)
     where
         X: XConn,
         C: RuntimeConfig,
     {
-        todo!()
-        /*
-         * 1. Actually apply the geometry
-         * 2. Update EWMH properties on the server, if needed
-         */
+        if let Some(win) = self.windows.focused() {
+            let (id, fullscreen) = (win.id(), win.is_fullscreen());
+            self.set_fullscreen(id, !fullscreen, conn, scr, cfg);
+        }
+    }
+
+    /// Puts a window into or takes it out of fullscreen.
+    ///
+    /// A fullscreen window covers the entire screen, ignoring
+    /// any space reserved by docks, and is stacked above everything.
+    pub fn set_fullscreen<X, C>(
+        &mut self,
+        id: XWindowID,
+        fullscreen: bool,
+        conn: &X,
+        scr: &Screen,
+        cfg: &C,
+    ) where
+        X: XConn,
+        C: RuntimeConfig,
+    {
+        let Some(win) = self.windows.lookup_mut(id) else {
+            warn!("set_fullscreen: no window {} found in workspace", id);
+            return
+        };
+        if win.is_fullscreen() == fullscreen {
+            return
+        }
+
+        debug!("setting fullscreen of window {} to {}", id, fullscreen);
+        if fullscreen {
+            win.set_fullscreen(conn, scr.true_geom());
+        } else {
+            win.unset_fullscreen(conn, cfg.border_px());
+        }
+        self.focuses.bubble_to_top(id, &self.windows);
+        self.relayout(conn, scr, cfg);
     }
 
     /// Toggles the state of the currently focused window between off or in layout.
@@ -601,6 +684,38 @@ impl Workspace {
         }
     }
 
+    /// Restacks a window according to a stacking mode requested by its client.
+    ///
+    /// Requests to raise a window under layout above everything are
+    /// clamped to just below the topmost window off layout, so that
+    /// the stacking policy of the workspace is preserved.
+    pub fn restack_window<X: XConn>(&mut self, conn: &X, id: XWindowID, mode: StackMode) {
+        let Some(win) = self.windows.lookup(id) else {
+            return
+        };
+
+        let mode = match mode {
+            StackMode::Above(sibling) if !win.is_off_layout() => {
+                let sibling_floats = sibling
+                    .and_then(|s| self.windows.lookup(s))
+                    .is_some_and(|s| s.is_off_layout());
+                match self.focuses.off_layout(&self.windows).last() {
+                    Some(floater) if sibling.is_none() || sibling_floats => {
+                        debug!("clamping window {} to below floater {}", id, floater);
+                        StackMode::Below(Some(*floater))
+                    }
+                    _ => mode,
+                }
+            }
+            mode => mode,
+        };
+
+        if mode.is_above() {
+            self.focuses.bubble_to_top(id, &self.windows);
+        }
+        win.configure(conn, &[ClientConfig::StackingMode(mode)]);
+    }
+
     /// Sends an update to the currently focused layout, and applies
     /// and changes that may have taken place.
     pub fn update_focused_layout<U: IntoUpdate, X, C>(
@@ -652,7 +767,9 @@ impl Workspace {
         // Set supported protocols
         window.set_supported(conn);
         // Configure window with a border width
-        window.configure(conn, &[ClientConfig::BorderWidth(cfg.border_px())]);
+        let border_px = window.border_px().unwrap_or(cfg.border_px());
+        window.set_border_width(conn, border_px);
+        self.place_transient(conn, &mut window);
 
         // add the window to internal client storage
         let id = window.id();
@@ -671,12 +788,7 @@ impl Workspace {
         // map window
         self.windows.lookup_mut(id).unwrap().map(conn);
 
-        // set input focus
-        if let Some(curr_focused) = self.windows.focused_mut() {
-            let to_unfocus = curr_focused.id();
-            self.unfocus_window(to_unfocus, conn, cfg);
-        }
-
+        // set input focus, this also unfocuses the current window
         self.focus_window(id, conn, cfg);
     }
 
@@ -698,6 +810,7 @@ impl Workspace {
             panic!("AAAAAA"); //fixme
         };
         self.focuses.remove_by_id(id);
+        self.orphan_transients(id);
 
         // the ClientRing should cycle to a new focused when remove our window
         if let Some(win) = self.windows.focused() {
@@ -720,13 +833,47 @@ impl Workspace {
         self.focuses.add_by_layout_status(id, &self.windows);
     }
 
+    /// Drops a window that no longer exists on the server,
+    /// without calling the layout.
+    pub(crate) fn forget_window(&mut self, window: XWindowID) -> Option<Client> {
+        self.focuses.remove_by_id(window);
+        self.orphan_transients(window);
+        self.windows.remove_by_id(window)
+    }
+
     /// Takes a window directly without calling the layout.
     pub(crate) fn take_window<X: XConn>(&mut self, window: XWindowID, conn: &X) -> Option<Client> {
         let mut window = self.windows.remove_by_id(window)?;
+        self.focuses.remove_by_id(window.id());
         window.unmap(conn);
         Some(window)
     }
 
+    /// Adds a client without mapping it or laying it out, for when
+    /// the workspace is not being shown.
+    ///
+    /// The client is mapped when the workspace is next activated.
+    pub(crate) fn stash_window<X, C>(&mut self, mut window: Client, conn: &X, cfg: &C)
+    where
+        X: XConn,
+        C: RuntimeConfig,
+    {
+        window.set_supported(conn);
+        let border_px = window.border_px().unwrap_or(cfg.border_px());
+        window.set_border_width(conn, border_px);
+        self.place_transient(conn, &mut window);
+        self.put_window(window);
+    }
+
+    /// Returns the IDs of all the windows that are transient for `parent`.
+    pub(crate) fn transients_of(&self, parent: XWindowID) -> Vec<XWindowID> {
+        self.windows
+            .iter()
+            .filter(|c| c.transient_for() == Some(parent))
+            .map(|c| c.id())
+            .collect()
+    }
+
     /// Updates the focus to the window under the pointer.
     pub(crate) fn focus_window_by_ptr<X, C>(&mut self, conn: &X, scr: &Screen, cfg: &C)
     where
@@ -741,12 +888,38 @@ impl Workspace {
         self.focus_window(reply.child, conn, cfg);
     }
 
-    fn apply_layout<X: XConn>(&mut self, conn: &X, layouts: Vec<LayoutAction>) {
+    fn apply_layout<X, C>(&mut self, conn: &X, cfg: &C, layouts: Vec<LayoutAction>)
+    where
+        X: XConn,
+        C: RuntimeConfig,
+    {
+        let size_hints = self.layouts.focused().is_some_and(|l| l.honours_size_hints());
+
+        /* each window ends up with the last border the layout set for it,
+        or its own otherwise, which gives back any borders taken away
+        by the layout before this one */
+        for win in self.windows.iter_mut().filter(|c| !c.is_fullscreen()) {
+            let id = win.id();
+            let width = layouts
+                .iter()
+                .rev()
+                .find_map(|a| match a {
+                    LayoutAction::SetBorder { id: target, width } if *target == id => Some(*width),
+                    _ => None,
+                })
+                .unwrap_or(win.border_px().unwrap_or(cfg.border_px()));
+            win.set_border_width(conn, width);
+        }
+
         for rsaction in layouts {
             match rsaction {
                 LayoutAction::Resize { id, geom } => {
                     let window = self.windows.lookup_mut(id).unwrap();
-                    window.set_and_update_geometry(conn, geom);
+                    if size_hints {
+                        window.set_and_update_geometry(conn, geom);
+                    } else {
+                        window.force_geometry(conn, geom);
+                    }
                 }
                 LayoutAction::Map(id) => {
                     let window = self.windows.lookup_mut(id).unwrap();
@@ -757,8 +930,13 @@ impl Workspace {
                     window.unmap(conn);
                 }
                 LayoutAction::StackOnTop(id) => {
-                    self.focuses.bubble_to_top(id, &self.windows);
+                    self.stack_window(conn, id);
+                }
+                LayoutAction::Focus(id) => {
+                    self.focus_window(id, conn, cfg);
                 }
+                // borders are all set above
+                LayoutAction::SetBorder { .. } => {}
                 LayoutAction::Remove(id) => {
                     let window = self.windows.lookup_mut(id).unwrap();
                     window.set_off_layout();
@@ -772,6 +950,34 @@ impl Workspace {
         for floater in self.floaters_rev_mut() {
             floater.configure(conn, &[ClientConfig::StackingMode(StackMode::Above(None))]);
         }
+
+        // fullscreen windows go above even the floaters
+        for fullscreen in self.windows.iter().filter(|c| c.is_fullscreen()) {
+            fullscreen.configure(conn, &[ClientConfig::StackingMode(StackMode::Above(None))]);
+        }
+    }
+
+    /// Centres a floating window over its parent, if it is a
+    /// transient for a window in this workspace.
+    fn place_transient<X: XConn>(&self, conn: &X, window: &mut Client) {
+        if !window.is_off_layout() {
+            return
+        }
+        let Some(parent) = window.transient_for().and_then(|p| self.windows.lookup(p)) else {
+            return
+        };
+        window.centre_over(parent.geometry());
+        window.update_geometry(conn);
+    }
+
+    /// Detaches the transients of a window that is going away,
+    /// so they become normal floating windows.
+    fn orphan_transients(&mut self, parent: XWindowID) {
+        for window in self.windows.iter_mut() {
+            if window.transient_for() == Some(parent) {
+                window.set_transient_for(None);
+            }
+        }
     }
 
     fn floaters_rev_mut(&mut self) -> impl Iterator<Item = &mut Client> {
@@ -794,6 +1000,35 @@ impl Workspace {
         conn.change_window_attributes(window, &[ClientAttrs::DisableClientEvents])
             .unwrap_or_else(|e| warn!("{}", e));
 
+        self.stack_window(conn, window);
+
+        //? naive approach if we need it
+        // this just stacks every window instead of doing checks
+        // for c in self.focuses.iter_rev() {
+
+        // }
+
+        let win = self.windows.lookup_mut(window).unwrap();
+        // the window has our attention now
+        if win.is_urgent() {
+            win.set_urgent(conn, cfg, false);
+        }
+        //* focus to current window visually...
+        win.set_border(conn, cfg.border_style(BorderStyle::Focused));
+        //* ...server-ly...
+        win.take_focus(conn);
+        //* ...and internally
+        self.windows.set_focused_by_winid(window);
+        self.focuses.set_focused_by_winid(window);
+
+        // re-enable events
+        conn.change_window_attributes(window, &[ClientAttrs::EnableClientEvents])
+            .unwrap_or_else(|e| error!("{}", e));
+    }
+
+    /// Stacks a window at the top of its layer, so that windows
+    /// under layout never go above windows off layout.
+    fn stack_window<X: XConn>(&mut self, conn: &X, window: XWindowID) {
         // move window to the top of its layer in the stacking order
         self.focuses.bubble_to_top(window, &self.windows);
 
@@ -830,24 +1065,9 @@ impl Workspace {
             win.configure(conn, &[ClientConfig::StackingMode(StackMode::Above(None))]);
         }
 
-        //? naive approach if we need it
-        // this just stacks every window instead of doing checks
-        // for c in self.focuses.iter_rev() {
-
-        // }
-
-        let win = self.windows.lookup_mut(window).unwrap();
-        //* focus to current window visually...
-        win.set_border(conn, cfg.border_style(BorderStyle::Focused));
-        //* ...server-ly...
-        conn.set_input_focus(window)
-            .unwrap_or_else(|e| warn!("{}", e));
-        //* ...and internally
-        self.windows.set_focused_by_winid(window);
-        self.focuses.set_focused_by_winid(window);
-
-        // re-enable events
-        conn.change_window_attributes(window, &[ClientAttrs::EnableClientEvents])
-            .unwrap_or_else(|e| error!("{}", e));
+        // keep the window's dialogs above it
+        for transient in self.windows.iter().filter(|c| c.transient_for() == Some(window)) {
+            transient.configure(conn, &[ClientConfig::StackingMode(StackMode::Above(None))]);
+        }
     }
 }
@@ -0,0 +1,446 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+use tracing::debug;
+
+use super::{
+    update::{Flip, InsertSide, ResizeSplit, Rotate, Update},
+    Layout, LayoutAction, LayoutCtxt, LayoutType,
+};
+
+use crate::types::{Cardinal, Geometry};
+use crate::backend::x::{XWindowID, Xid};
+
+/// A binary space partitioning layout.
+///
+/// Windows are kept in a binary tree, where each window is a leaf
+/// and each internal node splits its space in two. Each new window
+/// splits the space of the focused window along its longer side,
+/// unless a side to insert it on has been chosen with [`InsertSide`].
+///
+/// The split holding the focused window can be changed with the
+/// [`Rotate`], [`Flip`] and [`ResizeSplit`] updates.
+#[derive(Debug, Clone, Default)]
+pub struct Bsp {
+    // the root of the tree, if there are any windows
+    root: RefCell<Option<Node>>,
+    // the side to insert the next window on, if chosen
+    presel: Cell<Option<Cardinal>>,
+    // changes to the focused split, applied on the next layout
+    pending: RefCell<Vec<SplitOp>>,
+}
+
+impl Bsp {
+    /// Creates a new, empty BSP layout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Layout for Bsp {
+    fn name(&self) -> &str {
+        "BSP"
+    }
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        self._layout(ctxt)
+    }
+
+    fn boxed(&self) -> Box<dyn Layout> {
+        Box::new(self.clone())
+    }
+
+    /* the focused window is only known when laying out,
+    so changes to the focused split are queued until then */
+    fn receive_update(&self, update: &Update) {
+        if let Some(Rotate) = update.as_update() {
+            self.pending.borrow_mut().push(SplitOp::Rotate);
+        } else if let Some(Flip) = update.as_update() {
+            self.pending.borrow_mut().push(SplitOp::Flip);
+        } else if let Some(ResizeSplit(inc)) = update.as_update() {
+            self.pending.borrow_mut().push(SplitOp::Resize(*inc));
+        } else if let Some(InsertSide(side)) = update.as_update() {
+            self.presel.set(Some(*side));
+        }
+    }
+
+    fn style(&self) -> LayoutType {
+        LayoutType::Tiled
+    }
+
+    fn save_params(&self) -> Option<String> {
+        self.root.borrow().as_ref().map(|root| root.to_string())
+    }
+
+    fn restore_params(&self, params: &str) {
+        // windows that no longer exist are dropped on the next layout
+        match Node::parse(params) {
+            Some((root, "")) => *self.root.borrow_mut() = Some(root),
+            _ => debug!("could not parse BSP tree {:?}", params),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl Bsp {
+    fn _layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let ws = ctxt.workspace;
+        let ids: Vec<XWindowID> = ws.clients_in_layout().map(|c| c.id()).collect();
+        let mut root = self.root.borrow_mut();
+
+        // drop windows that were removed or taken off the layout
+        let mut leaves = Vec::new();
+        if let Some(root) = root.as_ref() {
+            root.leaves(&mut leaves);
+        }
+        for id in leaves.iter().filter(|id| !ids.contains(id)) {
+            debug!("removing {} from the tree", id);
+            *root = root.take().and_then(|node| node.remove(*id));
+        }
+        leaves.retain(|id| ids.contains(id));
+
+        // then split the focused window for each new one
+        let geom = ctxt.screen.effective_geom();
+        let mut target = ws
+            .focused_client()
+            .map(|c| c.id())
+            .filter(|id| leaves.contains(id))
+            .or(leaves.last().copied());
+        for id in ids.iter().filter(|id| !leaves.contains(id)) {
+            match (root.as_mut(), target) {
+                (Some(node), Some(target)) => {
+                    debug!("splitting {} for {}", target, id);
+                    node.insert(target, *id, geom, self.presel.take());
+                }
+                _ => *root = Some(Node::Leaf(*id)),
+            }
+            target = Some(*id);
+        }
+
+        let Some(root) = root.as_mut() else {
+            self.pending.borrow_mut().clear();
+            return Vec::new()
+        };
+
+        if let Some(focused) = ws.focused_client().map(|c| c.id()) {
+            for op in self.pending.borrow_mut().drain(..) {
+                if let Some(split) = root.parent_of(focused) {
+                    split.apply(op);
+                }
+            }
+        }
+        self.pending.borrow_mut().clear();
+
+        let mut geoms = Vec::new();
+        root.geometries(geom, &mut geoms);
+        geoms
+            .into_iter()
+            .map(|(id, geom)| LayoutAction::Resize {
+                id,
+                geom: ctxt.fit_border(id, geom),
+            })
+            .collect()
+    }
+}
+
+/// The direction that a split divides its space in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    /// Left and right, with `split_vert_ratio`.
+    Vertical,
+    /// Top and bottom, with `split_horz_ratio`.
+    Horizontal,
+}
+
+impl Axis {
+    fn split(self, geom: Geometry, ratio: f32) -> (Geometry, Geometry) {
+        match self {
+            Self::Vertical => geom.split_vert_ratio(ratio),
+            Self::Horizontal => geom.split_horz_ratio(ratio),
+        }
+    }
+}
+
+/// A change to a split, queued by an update.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SplitOp {
+    Rotate,
+    Flip,
+    Resize(f32),
+}
+
+/// A node of the BSP tree.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Leaf(XWindowID),
+    Split {
+        axis: Axis,
+        // the proportion of space that the first child takes up
+        ratio: f32,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+impl Node {
+    /// Appends the windows under this node to `out`, in order.
+    fn leaves(&self, out: &mut Vec<XWindowID>) {
+        match self {
+            Self::Leaf(id) => out.push(*id),
+            Self::Split { first, second, .. } => {
+                first.leaves(out);
+                second.leaves(out);
+            }
+        }
+    }
+
+    /// Removes a window, giving its space to its sibling.
+    ///
+    /// Returns `None` if nothing is left.
+    fn remove(self, id: XWindowID) -> Option<Node> {
+        match self {
+            Self::Leaf(leaf) if leaf == id => None,
+            Self::Leaf(_) => Some(self),
+            Self::Split { axis, ratio, first, second } => {
+                match (first.remove(id), second.remove(id)) {
+                    (Some(first), Some(second)) => Some(Self::Split {
+                        axis,
+                        ratio,
+                        first: Box::new(first),
+                        second: Box::new(second),
+                    }),
+                    (Some(node), None) | (None, Some(node)) => Some(node),
+                    (None, None) => None,
+                }
+            }
+        }
+    }
+
+    /// Splits the space of `target`, which is laid out over `geom`,
+    /// to make room for `new`.
+    ///
+    /// Returns whether `target` was found.
+    fn insert(
+        &mut self,
+        target: XWindowID,
+        new: XWindowID,
+        geom: Geometry,
+        side: Option<Cardinal>,
+    ) -> bool {
+        use Cardinal::*;
+
+        match self {
+            Self::Leaf(id) if *id == target => {
+                let (axis, new_first) = match side {
+                    Some(Left) => (Axis::Vertical, true),
+                    Some(Right) => (Axis::Vertical, false),
+                    Some(Up) => (Axis::Horizontal, true),
+                    Some(Down) => (Axis::Horizontal, false),
+                    None if geom.width >= geom.height => (Axis::Vertical, false),
+                    None => (Axis::Horizontal, false),
+                };
+                let (old, new) = (Box::new(Self::Leaf(target)), Box::new(Self::Leaf(new)));
+                let (first, second) = if new_first { (new, old) } else { (old, new) };
+                *self = Self::Split { axis, ratio: 0.5, first, second };
+                true
+            }
+            Self::Leaf(_) => false,
+            Self::Split { axis, ratio, first, second } => {
+                let (g1, g2) = axis.split(geom, *ratio);
+                first.insert(target, new, g1, side) || second.insert(target, new, g2, side)
+            }
+        }
+    }
+
+    /// Tests whether `id` is one of the direct children of this node.
+    fn is_parent_of(&self, id: XWindowID) -> bool {
+        match self {
+            Self::Leaf(_) => false,
+            Self::Split { first, second, .. } => {
+                [first, second].iter().any(|n| matches!(***n, Self::Leaf(leaf) if leaf == id))
+            }
+        }
+    }
+
+    /// Returns the split directly holding the window `id`.
+    fn parent_of(&mut self, id: XWindowID) -> Option<&mut Node> {
+        if self.is_parent_of(id) {
+            return Some(self)
+        }
+        match self {
+            Self::Leaf(_) => None,
+            Self::Split { first, second, .. } => {
+                first.parent_of(id).or_else(|| second.parent_of(id))
+            }
+        }
+    }
+
+    fn apply(&mut self, op: SplitOp) {
+        let Self::Split { axis, ratio, first, second } = self else {
+            return
+        };
+        match op {
+            SplitOp::Rotate => {
+                *axis = match axis {
+                    Axis::Vertical => Axis::Horizontal,
+                    Axis::Horizontal => Axis::Vertical,
+                }
+            }
+            SplitOp::Flip => std::mem::swap(first, second),
+            SplitOp::Resize(inc) => *ratio = (*ratio + inc).clamp(0.1, 0.9),
+        }
+    }
+
+    /// Appends the geometry of each window under this node to `out`,
+    /// when the node is laid out over `geom`.
+    fn geometries(&self, geom: Geometry, out: &mut Vec<(XWindowID, Geometry)>) {
+        match self {
+            Self::Leaf(id) => out.push((*id, geom)),
+            Self::Split { axis, ratio, first, second } => {
+                let (g1, g2) = axis.split(geom, *ratio);
+                first.geometries(g1, out);
+                second.geometries(g2, out);
+            }
+        }
+    }
+
+    /// Parses a node written out by its `Display` implementation,
+    /// returning it along with the rest of the input.
+    fn parse(s: &str) -> Option<(Node, &str)> {
+        let axis = match s.chars().next()? {
+            'v' => Axis::Vertical,
+            'h' => Axis::Horizontal,
+            _ => {
+                let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+                return Some((Self::Leaf(Xid(s[..end].parse().ok()?)), &s[end..]))
+            }
+        };
+        let (ratio, rest) = s[1..].split_once('(')?;
+        let (first, rest) = Self::parse(rest)?;
+        let (second, rest) = Self::parse(rest.strip_prefix(',')?)?;
+        let node = Self::Split {
+            axis,
+            ratio: ratio.parse().ok()?,
+            first: Box::new(first),
+            second: Box::new(second),
+        };
+        Some((node, rest.strip_prefix(')')?))
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Leaf(id) => write!(f, "{}", id.val()),
+            Self::Split { axis, ratio, first, second } => {
+                let axis = match axis {
+                    Axis::Vertical => 'v',
+                    Axis::Horizontal => 'h',
+                };
+                write!(f, "{}{}({},{})", axis, ratio, first, second)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Geometry = Geometry { x: 0, y: 0, height: 1080, width: 1920 };
+
+    fn geometries(node: &Node) -> Vec<(XWindowID, Geometry)> {
+        let mut out = Vec::new();
+        node.geometries(SCREEN, &mut out);
+        out
+    }
+
+    #[test]
+    fn test_node_insert() {
+        let mut root = Node::Leaf(Xid(1));
+        assert!(root.insert(Xid(1), Xid(2), SCREEN, None));
+        // the second window now has a taller space, so it is split across
+        assert!(root.insert(Xid(2), Xid(3), SCREEN, None));
+        assert!(!root.insert(Xid(4), Xid(5), SCREEN, None));
+
+        assert_eq!(
+            geometries(&root),
+            [
+                (Xid(1), Geometry::new(0, 0, 1080, 960)),
+                (Xid(2), Geometry::new(960, 0, 540, 960)),
+                (Xid(3), Geometry::new(960, 540, 540, 960)),
+            ]
+        );
+
+        // a chosen side puts the new window there
+        assert!(root.insert(Xid(1), Xid(4), SCREEN, Some(Cardinal::Up)));
+        assert_eq!(
+            geometries(&root)[..2],
+            [
+                (Xid(4), Geometry::new(0, 0, 540, 960)),
+                (Xid(1), Geometry::new(0, 540, 540, 960)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_node_remove() {
+        let mut root = Node::Leaf(Xid(1));
+        root.insert(Xid(1), Xid(2), SCREEN, None);
+        root.insert(Xid(2), Xid(3), SCREEN, None);
+
+        // the sibling of a removed window takes its space
+        let root = root.remove(Xid(2)).unwrap();
+        assert_eq!(
+            geometries(&root),
+            [
+                (Xid(1), Geometry::new(0, 0, 1080, 960)),
+                (Xid(3), Geometry::new(960, 0, 1080, 960)),
+            ]
+        );
+
+        let root = root.remove(Xid(1)).unwrap();
+        assert_eq!(root, Node::Leaf(Xid(3)));
+        assert_eq!(root.remove(Xid(3)), None);
+    }
+
+    #[test]
+    fn test_node_split_ops() {
+        let mut root = Node::Leaf(Xid(1));
+        root.insert(Xid(1), Xid(2), SCREEN, None);
+        root.insert(Xid(2), Xid(3), SCREEN, None);
+
+        let split = root.parent_of(Xid(3)).unwrap();
+        split.apply(SplitOp::Rotate);
+        split.apply(SplitOp::Flip);
+        split.apply(SplitOp::Resize(0.25));
+        assert_eq!(
+            geometries(&root)[1..],
+            [
+                (Xid(3), Geometry::new(960, 0, 1080, 720)),
+                (Xid(2), Geometry::new(1680, 0, 1080, 240)),
+            ]
+        );
+
+        // ratios stay within bounds
+        root.apply(SplitOp::Resize(-1.0));
+        assert_eq!(geometries(&root)[0], (Xid(1), Geometry::new(0, 0, 1080, 192)));
+        assert!(root.parent_of(Xid(4)).is_none());
+    }
+
+    #[test]
+    fn test_node_parse() {
+        let mut root = Node::Leaf(Xid(1));
+        root.insert(Xid(1), Xid(2), SCREEN, None);
+        root.insert(Xid(2), Xid(3), SCREEN, Some(Cardinal::Up));
+
+        let saved = root.to_string();
+        assert_eq!(saved, "v0.5(1,h0.5(3,2))");
+        assert_eq!(Node::parse(&saved), Some((root, "")));
+        assert_eq!(Node::parse("7 rest"), Some((Node::Leaf(Xid(7)), " rest")));
+
+        for bad in ["", "v0.5(1,2", "h(1,2)", "v0.5(1;2)", "x"] {
+            assert_eq!(Node::parse(bad), None, "parsed {:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::cell::Cell;
+
+use super::{
+    update::{ResizeMain, Update},
+    Layout, LayoutAction, LayoutCtxt, LayoutType,
+};
+
+use crate::types::Geometry;
+use crate::backend::x::XWindowID;
+
+/// A layout with the main window floating in the middle of the screen,
+/// above the other windows.
+///
+/// The main window is the first window under layout. The other
+/// windows tile the whole screen behind it, in two stacks, with the
+/// one on the left holding the first half of them.
+///
+/// The size of the main window, as a proportion of the screen,
+/// can be changed with [`ResizeMain`].
+#[derive(Debug, Clone)]
+pub struct CenteredMaster {
+    // the proportion of the screen that the main window takes up
+    ratio: Cell<f32>,
+}
+
+impl CenteredMaster {
+    /// Creates a new CenteredMaster layout, with the given ratio
+    /// of the screen's width and height taken up by the main window.
+    pub fn new(ratio: f32) -> Self {
+        Self {
+            ratio: Cell::new(ratio),
+        }
+    }
+}
+
+impl Default for CenteredMaster {
+    fn default() -> Self {
+        Self::new(0.6)
+    }
+}
+
+impl Layout for CenteredMaster {
+    fn name(&self) -> &str {
+        "CenteredMaster"
+    }
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        self._layout(ctxt)
+    }
+
+    fn boxed(&self) -> Box<dyn Layout> {
+        Box::new(self.clone())
+    }
+
+    fn receive_update(&self, update: &Update) {
+        if let Some(ResizeMain(inc)) = update.as_update() {
+            self.ratio.set((self.ratio.get() + inc).clamp(0.1, 1.0));
+        }
+    }
+
+    fn style(&self) -> LayoutType {
+        LayoutType::Tiled
+    }
+
+    fn save_params(&self) -> Option<String> {
+        Some(self.ratio.get().to_string())
+    }
+
+    fn restore_params(&self, params: &str) {
+        if let Ok(ratio) = params.parse() {
+            self.ratio.set(ratio);
+        }
+    }
+}
+
+#[doc(hidden)]
+impl CenteredMaster {
+    fn _layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let ids: Vec<XWindowID> = ctxt.workspace.clients_in_layout().map(|c| c.id()).collect();
+        let Some(&main) = ids.first() else {
+            return Vec::new()
+        };
+        let geoms = Self::tiles(ctxt.screen.effective_geom(), self.ratio.get(), ids.len());
+
+        let mut ret: Vec<_> = ids
+            .iter()
+            .zip(geoms)
+            .map(|(&id, geom)| LayoutAction::Resize { id, geom: ctxt.fit_border(id, geom) })
+            .collect();
+        ret.push(LayoutAction::StackOnTop(main));
+        ret
+    }
+
+    /// Lays `n` windows out over `geom`, with the main window first.
+    fn tiles(geom: Geometry, ratio: f32, n: usize) -> Vec<Geometry> {
+        let stack = n.saturating_sub(1);
+        if stack == 0 {
+            return vec![geom; n]
+        }
+
+        let width = (geom.width as f32 * ratio) as i32;
+        let height = (geom.height as f32 * ratio) as i32;
+        let mut geoms = vec![Geometry::new(
+            geom.x + (geom.width - width) / 2,
+            geom.y + (geom.height - height) / 2,
+            height,
+            width,
+        )];
+
+        if stack == 1 {
+            geoms.push(geom);
+        } else {
+            let nleft = stack.div_ceil(2);
+            let sides = geom.split_vert_n(2);
+            geoms.extend(sides[0].split_horz_n(nleft));
+            geoms.extend(sides[1].split_horz_n(stack - nleft));
+        }
+        geoms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Geometry = Geometry { x: 0, y: 0, height: 1080, width: 1920 };
+
+    #[test]
+    fn test_centered_tiles() {
+        assert!(CenteredMaster::tiles(SCREEN, 0.6, 0).is_empty());
+        assert_eq!(CenteredMaster::tiles(SCREEN, 0.6, 1), [SCREEN]);
+
+        let main = Geometry::new(384, 216, 648, 1152);
+        assert_eq!(CenteredMaster::tiles(SCREEN, 0.6, 2), [main, SCREEN]);
+
+        // the stacks behind the main window split the whole screen
+        assert_eq!(
+            CenteredMaster::tiles(SCREEN, 0.6, 5),
+            [
+                main,
+                Geometry::new(0, 0, 540, 960),
+                Geometry::new(0, 540, 540, 960),
+                Geometry::new(960, 0, 540, 960),
+                Geometry::new(960, 540, 540, 960),
+            ]
+        );
+        assert_eq!(CenteredMaster::tiles(SCREEN, 1.0, 3)[0], SCREEN);
+    }
+}
@@ -0,0 +1,243 @@
+use std::cell::Cell;
+
+use super::{
+    update::{ResizeMain, Update, UpdateBorderPx},
+    Layout, LayoutAction, LayoutCtxt, LayoutType,
+};
+
+use crate::types::{Cardinal, Geometry};
+use crate::backend::x::XWindowID;
+
+/// A layout where each window takes part of the space left over by
+/// the windows before it, spiralling inwards.
+///
+/// The first window takes the left of the screen, the second the top
+/// of what is left, the third the right of what is left after that,
+/// and so on, going around clockwise.
+///
+/// The proportion of the space each window takes can be changed
+/// with [`ResizeMain`].
+#[derive(Debug, Clone)]
+pub struct Spiral(Fibonacci);
+
+impl Spiral {
+    /// Creates a new Spiral layout, with the given ratio
+    /// and border width setup.
+    pub fn new(ratio: f32, bwidth: u32) -> Self {
+        Self(Fibonacci::new(ratio, bwidth))
+    }
+}
+
+/// A layout where each window takes part of the space left over by
+/// the windows before it, dwindling towards the bottom right.
+///
+/// The first window takes the left of the screen, the second the top
+/// of what is left, the third the left of what is left after that,
+/// and so on.
+///
+/// The proportion of the space each window takes can be changed
+/// with [`ResizeMain`].
+#[derive(Debug, Clone)]
+pub struct Dwindle(Fibonacci);
+
+impl Dwindle {
+    /// Creates a new Dwindle layout, with the given ratio
+    /// and border width setup.
+    pub fn new(ratio: f32, bwidth: u32) -> Self {
+        Self(Fibonacci::new(ratio, bwidth))
+    }
+}
+
+macro_rules! impl_fibonacci_layout {
+    ($ty:ident, $name:literal, $spiral:literal) => {
+        impl Layout for $ty {
+            fn name(&self) -> &str {
+                $name
+            }
+
+            fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+                self.0.layout(ctxt, $spiral)
+            }
+
+            fn boxed(&self) -> Box<dyn Layout> {
+                Box::new(self.clone())
+            }
+
+            fn receive_update(&self, update: &Update) {
+                self.0.receive_update(update)
+            }
+
+            fn style(&self) -> LayoutType {
+                LayoutType::Tiled
+            }
+
+            fn save_params(&self) -> Option<String> {
+                Some(self.0.ratio.get().to_string())
+            }
+
+            fn restore_params(&self, params: &str) {
+                if let Ok(ratio) = params.parse() {
+                    self.0.ratio.set(ratio);
+                }
+            }
+        }
+    };
+}
+
+impl_fibonacci_layout!(Spiral, "Spiral", true);
+impl_fibonacci_layout!(Dwindle, "Dwindle", false);
+
+/// The state shared by `Spiral` and `Dwindle`.
+#[derive(Debug, Clone)]
+struct Fibonacci {
+    // the proportion of the space left that each window takes up
+    ratio: Cell<f32>,
+    // the border width of windows that do not set their own
+    bwidth: Cell<u32>,
+}
+
+impl Fibonacci {
+    fn new(ratio: f32, bwidth: u32) -> Self {
+        Self {
+            ratio: Cell::new(ratio),
+            bwidth: Cell::new(bwidth),
+        }
+    }
+
+    fn receive_update(&self, update: &Update) {
+        if let Some(ResizeMain(inc)) = update.as_update() {
+            self.ratio.set((self.ratio.get() + inc).clamp(0.1, 0.9));
+        } else if let Some(UpdateBorderPx(new)) = update.as_update() {
+            self.bwidth.set(*new);
+        }
+    }
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>, spiral: bool) -> Vec<LayoutAction> {
+        let ids: Vec<XWindowID> = ctxt.workspace.clients_in_layout().map(|c| c.id()).collect();
+        let geoms = Self::tiles(ctxt.screen.effective_geom(), self.ratio.get(), ids.len(), spiral);
+        let bwidth = self.bwidth.get();
+
+        ids.into_iter()
+            .zip(geoms)
+            .map(|(id, geom)| LayoutAction::Resize {
+                id,
+                geom: ctxt.fit_border_or(id, geom, bwidth),
+            })
+            .collect()
+    }
+
+    /// Lays `n` windows out over `geom`, each taking `ratio`
+    /// of the space left by the ones before it.
+    fn tiles(geom: Geometry, ratio: f32, n: usize, spiral: bool) -> Vec<Geometry> {
+        use Cardinal::*;
+
+        let mut left = geom;
+        let mut ret = Vec::new();
+        for i in 0..n {
+            // the last window takes whatever is left
+            if i + 1 == n {
+                ret.push(left);
+                break
+            }
+            /* dwindling only ever takes the left or the top,
+            while spiralling also goes around to the right and bottom */
+            let side = match (i % 4, spiral) {
+                (0, _) | (2, false) => Left,
+                (1, _) | (3, false) => Up,
+                (2, true) => Right,
+                _ => Down,
+            };
+            let (geom, rest) = Self::split(left, ratio, side);
+            ret.push(geom);
+            left = rest;
+        }
+        ret
+    }
+
+    /// Splits off `ratio` of `geom` on the given side,
+    /// returning it and the rest of `geom`.
+    fn split(geom: Geometry, ratio: f32, side: Cardinal) -> (Geometry, Geometry) {
+        match side {
+            Cardinal::Left => geom.split_vert_ratio(ratio),
+            Cardinal::Up => geom.split_horz_ratio(ratio),
+            Cardinal::Right => {
+                let (rest, geom) = geom.split_vert_ratio(1.0 - ratio);
+                (geom, rest)
+            }
+            Cardinal::Down => {
+                let (rest, geom) = geom.split_horz_ratio(1.0 - ratio);
+                (geom, rest)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::layouts::tests::TestWorkspace;
+    use crate::layouts::update::IntoUpdate;
+
+    const SCREEN: Geometry = Geometry { x: 0, y: 0, height: 1080, width: 1920 };
+
+    #[test]
+    fn test_dwindle_tiles() {
+        assert!(Fibonacci::tiles(SCREEN, 0.5, 0, false).is_empty());
+        assert_eq!(Fibonacci::tiles(SCREEN, 0.5, 1, false), [SCREEN]);
+        assert_eq!(
+            Fibonacci::tiles(SCREEN, 0.5, 4, false),
+            [
+                Geometry::new(0, 0, 1080, 960),
+                Geometry::new(960, 0, 540, 960),
+                Geometry::new(960, 540, 540, 480),
+                Geometry::new(1440, 540, 540, 480),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spiral_tiles() {
+        // spiralling goes around to the right on the third window
+        assert_eq!(
+            Fibonacci::tiles(SCREEN, 0.5, 5, true),
+            [
+                Geometry::new(0, 0, 1080, 960),
+                Geometry::new(960, 0, 540, 960),
+                Geometry::new(1440, 540, 540, 480),
+                Geometry::new(960, 810, 270, 480),
+                Geometry::new(960, 540, 270, 480),
+            ]
+        );
+        assert_eq!(
+            Fibonacci::tiles(SCREEN, 0.75, 2, true),
+            [Geometry::new(0, 0, 1080, 1440), Geometry::new(1440, 0, 1080, 480)]
+        );
+    }
+
+    #[test]
+    fn test_fibonacci_borders() {
+        let mut t = TestWorkspace::new(2);
+        let ids = t.ids();
+        let layout = Dwindle::new(0.5, 2);
+        let geoms = |t: &TestWorkspace| -> Vec<_> {
+            layout
+                .layout(t.ctxt())
+                .into_iter()
+                .filter_map(|a| match a {
+                    LayoutAction::Resize { geom, .. } => Some(geom),
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!(geoms(&t)[0], Geometry::new(0, 0, 1076, 956));
+
+        // the border width is the one last sent, unless a window sets its own
+        layout.receive_update(&UpdateBorderPx(5).into_update());
+        t.ws.windows.lookup_mut(ids[1]).unwrap().set_border_px(Some(0));
+        assert_eq!(
+            geoms(&t),
+            [Geometry::new(0, 0, 1070, 950), Geometry::new(960, 0, 1080, 960)]
+        );
+    }
+}
@@ -32,4 +32,8 @@ impl Layout for Floating {
     fn style(&self) -> LayoutType {
         LayoutType::Floating
     }
+
+    fn honours_size_hints(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,160 @@
+use std::cell::Cell;
+
+use super::{
+    update::{DecColumns, IncColumns, Update},
+    Layout, LayoutAction, LayoutCtxt, LayoutType,
+};
+
+use crate::types::Geometry;
+use crate::backend::x::XWindowID;
+
+/// A layout that arranges windows in a grid.
+///
+/// By default, the grid has as many columns as makes it the
+/// squarest for the number of windows. This can be changed
+/// with the [`IncColumns`] and [`DecColumns`] updates.
+/// Where the windows do not fill the grid, the columns on
+/// the right hold one window fewer.
+#[derive(Debug, Clone, Default)]
+pub struct Grid {
+    // the number of columns set by the user, if any
+    columns: Cell<Option<u32>>,
+    // the number of columns in the last layout
+    last: Cell<u32>,
+}
+
+impl Grid {
+    /// Creates a new Grid layout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of columns for the squarest grid holding `n` windows.
+    fn squarest(n: usize) -> usize {
+        (1..=n).find(|cols| cols * cols >= n).unwrap_or(1)
+    }
+}
+
+impl Layout for Grid {
+    fn name(&self) -> &str {
+        "Grid"
+    }
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        self._layout(ctxt)
+    }
+
+    fn boxed(&self) -> Box<dyn Layout> {
+        Box::new(self.clone())
+    }
+
+    fn receive_update(&self, update: &Update) {
+        let current = self.columns.get().unwrap_or(self.last.get());
+        if let Some(IncColumns(inc)) = update.as_update() {
+            self.columns.set(Some(current.saturating_add(*inc)));
+        } else if let Some(DecColumns(dec)) = update.as_update() {
+            self.columns.set(Some(current.saturating_sub(*dec).max(1)));
+        }
+    }
+
+    fn style(&self) -> LayoutType {
+        LayoutType::Tiled
+    }
+
+    fn save_params(&self) -> Option<String> {
+        self.columns.get().map(|cols| cols.to_string())
+    }
+
+    fn restore_params(&self, params: &str) {
+        self.columns.set(params.parse().ok());
+    }
+}
+
+#[doc(hidden)]
+impl Grid {
+    fn _layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let ids: Vec<XWindowID> = ctxt.workspace.clients_in_layout().map(|c| c.id()).collect();
+        if ids.is_empty() {
+            return Vec::new()
+        }
+
+        let cols = self.columns_for(ids.len());
+        let cells = Self::cells(ctxt.screen.effective_geom(), ids.len(), cols);
+        ids.into_iter()
+            .zip(cells)
+            .map(|(id, geom)| LayoutAction::Resize { id, geom: ctxt.fit_border(id, geom) })
+            .collect()
+    }
+
+    /// The number of columns to lay `n` windows out in,
+    /// which is remembered for the next update.
+    fn columns_for(&self, n: usize) -> usize {
+        let cols = self
+            .columns
+            .get()
+            .map(|cols| cols as usize)
+            .unwrap_or(Self::squarest(n))
+            .clamp(1, n.max(1));
+        self.last.set(cols as u32);
+        cols
+    }
+
+    /// Splits `geom` into `n` cells over `cols` columns, going
+    /// down each column in turn.
+    fn cells(geom: Geometry, n: usize, cols: usize) -> Vec<Geometry> {
+        // the first columns take the windows left over
+        let (per_col, extra) = (n / cols, n % cols);
+        geom.split_vert_n(cols)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, column)| column.split_horz_n(per_col + (i < extra) as usize))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::layouts::update::IntoUpdate;
+
+    const SCREEN: Geometry = Geometry { x: 0, y: 0, height: 1080, width: 1920 };
+
+    #[test]
+    fn test_squarest() {
+        let cols: Vec<_> = (1..=10).map(Grid::squarest).collect();
+        assert_eq!(cols, [1, 2, 2, 2, 3, 3, 3, 3, 3, 4]);
+    }
+
+    #[test]
+    fn test_grid_cells() {
+        assert_eq!(
+            Grid::cells(SCREEN, 5, 3),
+            [
+                Geometry::new(0, 0, 540, 640),
+                Geometry::new(0, 540, 540, 640),
+                Geometry::new(640, 0, 540, 640),
+                Geometry::new(640, 540, 540, 640),
+                Geometry::new(1280, 0, 1080, 640),
+            ]
+        );
+        assert_eq!(Grid::cells(SCREEN, 1, 1), [SCREEN]);
+    }
+
+    #[test]
+    fn test_grid_columns() {
+        let grid = Grid::new();
+        assert_eq!(grid.columns_for(5), 3);
+
+        // changes are made from the last number of columns
+        grid.receive_update(&IncColumns(2).into_update());
+        assert_eq!(grid.columns_for(5), 5);
+        grid.receive_update(&DecColumns(10).into_update());
+        assert_eq!(grid.columns_for(5), 1);
+
+        // there are never more columns than windows
+        grid.receive_update(&IncColumns(9).into_update());
+        assert_eq!(grid.columns_for(3), 3);
+        assert_eq!(grid.save_params(), Some("10".into()));
+    }
+}
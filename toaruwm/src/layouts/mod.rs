@@ -9,20 +9,46 @@ use tracing::debug;
 
 use crate::core::{Ring, Screen, Workspace};
 use crate::manager::RuntimeConfig;
-use crate::types::Geometry;
+use crate::types::{Cardinal, Geometry};
 use crate::backend::x::XWindowID;
 use crate::{Result, ToaruError, XConn};
 
+/// A binary space partitioning layout.
+pub mod bsp;
+/// A layout with the main window floating in the middle.
+pub mod centered;
+/// Layouts where each window takes part of the space left over.
+pub mod fibonacci;
 /// A simple no-frills floating layout.
 pub mod floating;
+/// A layout arranging windows in a grid.
+pub mod grid;
+/// Layouts that wrap around another layout and change what it does.
+pub mod modifiers;
+/// A layout showing one window at a time.
+pub mod monocle;
+/// A layout with the main window between two stacks.
+pub mod threecol;
 /// A simple manually-tiled layout.
 pub mod tiled;
 /// Types to be used to update layouts.
 pub mod update;
 
+#[doc(inline)]
+pub use bsp::Bsp;
+#[doc(inline)]
+pub use centered::CenteredMaster;
+#[doc(inline)]
+pub use fibonacci::{Dwindle, Spiral};
 #[doc(inline)]
 pub use floating::Floating;
 #[doc(inline)]
+pub use grid::Grid;
+#[doc(inline)]
+pub use monocle::Monocle;
+#[doc(inline)]
+pub use threecol::ThreeColumn;
+#[doc(inline)]
 pub use tiled::DynamicTiled;
 
 use update::Update;
@@ -67,6 +93,16 @@ pub trait Layout {
     /// The name of the Layout, used to display in some kind of status bar.
     fn name(&self) -> &str;
 
+    /// The name of the Layout as it should be shown in a status bar,
+    /// given the workspace it is on.
+    ///
+    /// Layouts can use this to show some of their state, such as
+    /// which window is being shown. Since layouts are looked up by
+    /// `name`, it should not change. This defaults to `name`.
+    fn display_name(&self, _ws: &Workspace) -> String {
+        self.name().into()
+    }
+
     /// Return the style of the layout.
     fn style(&self) -> LayoutType;
 
@@ -83,13 +119,35 @@ pub trait Layout {
     /// This type does not need to respond to all possible updates,
     /// only the ones that specifically apply to it.
     fn receive_update(&self, update: &Update);
+
+    /// Returns the current parameters of the layout, so they can
+    /// be preserved across an in-place restart.
+    ///
+    /// The returned string is passed back to `restore_params`
+    /// after the restart, and must not contain tabs or newlines.
+    /// Layouts without any parameters do not need to implement this.
+    fn save_params(&self) -> Option<String> {
+        None
+    }
+
+    /// Restores parameters previously returned by `save_params`.
+    fn restore_params(&self, _params: &str) {}
+
+    /// Whether the geometries given to windows by this layout should
+    /// be constrained by their size hints (WM_NORMAL_HINTS).
+    ///
+    /// If so, windows may end up smaller than the space given to them.
+    /// Layouts fill the space exactly by default.
+    fn honours_size_hints(&self) -> bool {
+        false
+    }
 }
 
 use custom_debug_derive::Debug;
 /// The context providing any information that the layout may need
 /// to enforce its layout policy.
 #[non_exhaustive]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct LayoutCtxt<'wm> {
     //fixme: custom debug is just a bodge rn
     /// A Connection to the X server to make queries if needed.
@@ -104,6 +162,36 @@ pub struct LayoutCtxt<'wm> {
     pub screen: &'wm Screen,
 }
 
+impl LayoutCtxt<'_> {
+    /// The border width of a window, which is the configured one
+    /// unless the window sets its own.
+    pub fn border_px(&self, id: XWindowID) -> u32 {
+        self.workspace
+            .windows
+            .lookup(id)
+            .and_then(|c| c.border_px())
+            .unwrap_or(self.config.border_px())
+    }
+
+    /// Shrinks the space given to a window to make room for its border,
+    /// since the X server does not count the border in the window size.
+    pub fn fit_border(&self, id: XWindowID, geom: Geometry) -> Geometry {
+        self.fit_border_or(id, geom, self.config.border_px())
+    }
+
+    /// Like [`fit_border`](Self::fit_border), but with `default` as
+    /// the border width of windows that do not set their own.
+    pub fn fit_border_or(&self, id: XWindowID, geom: Geometry, default: u32) -> Geometry {
+        let bwidth = self
+            .workspace
+            .windows
+            .lookup(id)
+            .and_then(|c| c.border_px())
+            .unwrap_or(default) as i32;
+        geom.trim(bwidth * 2, Cardinal::Right).trim(bwidth * 2, Cardinal::Down)
+    }
+}
+
 /// A Ring of layouts applied on a workspace.
 ///
 /// A set of layouts that a workspace can use to apply on its
@@ -279,8 +367,87 @@ pub enum LayoutAction {
     Map(XWindowID),
     /// Unmap the given window.
     Unmap(XWindowID),
-    /// Stack the given window on top.
+    /// Stack the given window on top of the other windows
+    /// under layout, but below any windows off layout.
     StackOnTop(XWindowID),
+    /// Give the input focus to the given window.
+    Focus(XWindowID),
+    /// Set the border width of the given window.
+    SetBorder {
+        /// The Client to set the border width of.
+        id: XWindowID,
+        /// The new border width.
+        width: u32,
+    },
     /// Remove the given window from the layout.
     Remove(XWindowID),
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use crate::backend::x::{dummy::DummyConn, XWindow, Xid};
+    use crate::core::Client;
+    use crate::manager::{state::WmConfig, Config, ToaruConfig};
+
+    /// A workspace on a 1920x1080 screen, for running layouts on.
+    pub(crate) struct TestWorkspace {
+        pub(crate) conn: DummyConn,
+        pub(crate) config: WmConfig,
+        pub(crate) screen: Screen,
+        pub(crate) ws: Workspace,
+    }
+
+    impl TestWorkspace {
+        /// Creates a workspace holding `n` windows under layout.
+        pub(crate) fn new(n: usize) -> Self {
+            let screen = Geometry::new(0, 0, 1080, 1920);
+            let mut ret = Self {
+                conn: DummyConn::new(XWindow::with_data(Xid(1), screen)),
+                config: ToaruConfig::default().into_runtime_config(),
+                screen: Screen::new(0, screen, Xid(1), vec!["1".into()]),
+                ws: Workspace::new("1"),
+            };
+            for _ in 0..n {
+                ret.add(false);
+            }
+            ret
+        }
+
+        /// Adds a window, off layout if `floating` is set, and focuses it.
+        ///
+        /// Windows are added to the front of the workspace.
+        pub(crate) fn add(&mut self, floating: bool) -> XWindowID {
+            let id = self.conn.add_window(Geometry::new(0, 0, 100, 100));
+            let client = if floating {
+                Client::outside_layout(id, &self.conn)
+            } else {
+                Client::new(id, &self.conn)
+            };
+            self.ws.put_window(client);
+            self.focus(id);
+            id
+        }
+
+        /// Focuses a window without going through the X server.
+        pub(crate) fn focus(&mut self, id: XWindowID) {
+            self.ws.windows.set_focused_by_winid(id);
+            self.ws.focuses.set_focused_by_winid(id);
+        }
+
+        /// The windows under layout, in the order they are laid out.
+        pub(crate) fn ids(&self) -> Vec<XWindowID> {
+            self.ws.clients_in_layout().map(|c| c.id()).collect()
+        }
+
+        pub(crate) fn ctxt(&self) -> LayoutCtxt<'_> {
+            LayoutCtxt {
+                conn: &self.conn,
+                config: &self.config,
+                workspace: &self.ws,
+                screen: &self.screen,
+            }
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//! Each modifier takes any boxed [`Layout`] and post-processes the
+//! [`LayoutAction`]s it generates, so they can be stacked on top of
+//! each other:
+//!
+//! ```rust
+//! use toaruwm::layouts::{DynamicTiled, Layout};
+//! use toaruwm::layouts::modifiers::{Mirror, SmartBorders, Spacing};
+//!
+//! let layout = Spacing::new(
+//!     SmartBorders::new(Mirror::new(DynamicTiled::new(0.5, 2).boxed()).boxed()).boxed()
+//! ).with_gaps(4, 8);
+//!
+//! // modifiers take on the name of the layout they wrap
+//! assert_eq!(layout.name(), "DTiled");
+//! ```
+//!
+//! Modifiers forward their name, parameters and any updates they
+//! receive to the layout they wrap.
+
+use std::fmt;
+
+use super::{update::Update, Layout, LayoutAction, LayoutCtxt, LayoutType};
+
+use crate::core::Workspace;
+use crate::types::Geometry;
+use crate::backend::x::XWindowID;
+
+/// Forwards everything but `layout` and `boxed` to the inner layout.
+macro_rules! forward_to_inner {
+    () => {
+        fn name(&self) -> &str {
+            self.inner.name()
+        }
+
+        fn display_name(&self, ws: &Workspace) -> String {
+            self.inner.display_name(ws)
+        }
+
+        fn style(&self) -> LayoutType {
+            self.inner.style()
+        }
+
+        fn receive_update(&self, update: &Update) {
+            self.inner.receive_update(update)
+        }
+
+        fn save_params(&self) -> Option<String> {
+            self.inner.save_params()
+        }
+
+        fn restore_params(&self, params: &str) {
+            self.inner.restore_params(params)
+        }
+
+        fn honours_size_hints(&self) -> bool {
+            self.inner.honours_size_hints()
+        }
+    };
+}
+
+/// Adds gaps between windows, and between windows and the
+/// edges of the screen.
+///
+/// By default, both gaps are the configured `window_gap`.
+pub struct Spacing {
+    inner: Box<dyn Layout>,
+    // the inner and outer gaps, if set instead of the configured one
+    gaps: Option<(u32, u32)>,
+}
+
+impl Spacing {
+    /// Wraps `inner` with gaps of the configured `window_gap`.
+    pub fn new(inner: Box<dyn Layout>) -> Self {
+        Self { inner, gaps: None }
+    }
+
+    /// Sets the gap between windows, and the gap between
+    /// windows and the edges of the screen.
+    pub fn with_gaps(mut self, inner: u32, outer: u32) -> Self {
+        self.gaps = Some((inner, outer));
+        self
+    }
+}
+
+impl Layout for Spacing {
+    forward_to_inner!();
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let actions = self.inner.layout(ctxt);
+        let gap = ctxt.config.window_gap();
+        let (inner, outer) = self.gaps.unwrap_or((gap, gap));
+        if inner == 0 && outer == 0 {
+            return actions
+        }
+
+        let scr = ctxt.screen.effective_geom();
+        map_cells(&ctxt, actions, |cell| space(cell, scr, inner, outer))
+    }
+
+    fn boxed(&self) -> Box<dyn Layout> {
+        Box::new(Self {
+            inner: self.inner.boxed(),
+            gaps: self.gaps,
+        })
+    }
+}
+
+/// Removes the borders of windows when only one is visible.
+///
+/// This is when there is only one window under layout, or when
+/// every window takes up the same space, as in a monocle layout.
+pub struct SmartBorders {
+    inner: Box<dyn Layout>,
+}
+
+impl SmartBorders {
+    /// Wraps `inner` with smart borders.
+    pub fn new(inner: Box<dyn Layout>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Layout for SmartBorders {
+    forward_to_inner!();
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let actions = self.inner.layout(ctxt);
+        let mut tiles = actions.iter().filter_map(|a| match a {
+            LayoutAction::Resize { geom, .. } => Some(geom),
+            _ => None,
+        });
+        let alone = tiles.next().is_some_and(|first| tiles.all(|g| g == first));
+        // the workspace gives back the borders of every other window
+        if !alone {
+            return actions
+        }
+
+        let mut ret = Vec::new();
+        for action in actions {
+            if let LayoutAction::Resize { id, geom } = action {
+                ret.push(LayoutAction::SetBorder { id, width: 0 });
+                ret.push(LayoutAction::Resize { id, geom: expand(geom, ctxt.border_px(id)) });
+            } else {
+                ret.push(action);
+            }
+        }
+        ret
+    }
+
+    fn boxed(&self) -> Box<dyn Layout> {
+        Box::new(Self {
+            inner: self.inner.boxed(),
+        })
+    }
+}
+
+/// Swaps the horizontal and vertical axes of a layout.
+///
+/// For example, a layout with its main window on the left
+/// would have its main window on the top instead.
+pub struct Mirror {
+    inner: Box<dyn Layout>,
+}
+
+impl Mirror {
+    /// Wraps `inner`, swapping its axes.
+    pub fn new(inner: Box<dyn Layout>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Layout for Mirror {
+    forward_to_inner!();
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let actions = self.inner.layout(ctxt);
+        let scr = ctxt.screen.effective_geom();
+        map_cells(&ctxt, actions, |cell| mirror(cell, scr))
+    }
+
+    fn boxed(&self) -> Box<dyn Layout> {
+        Box::new(Self {
+            inner: self.inner.boxed(),
+        })
+    }
+}
+
+/// Flips a layout horizontally or vertically.
+pub struct Reflect {
+    inner: Box<dyn Layout>,
+    // whether to flip top to bottom instead of left to right
+    vertical: bool,
+}
+
+impl Reflect {
+    /// Wraps `inner`, flipping it from left to right.
+    pub fn horizontal(inner: Box<dyn Layout>) -> Self {
+        Self { inner, vertical: false }
+    }
+
+    /// Wraps `inner`, flipping it from top to bottom.
+    pub fn vertical(inner: Box<dyn Layout>) -> Self {
+        Self { inner, vertical: true }
+    }
+}
+
+impl Layout for Reflect {
+    forward_to_inner!();
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let actions = self.inner.layout(ctxt);
+        let scr = ctxt.screen.effective_geom();
+        map_cells(&ctxt, actions, |cell| reflect(cell, scr, self.vertical))
+    }
+
+    fn boxed(&self) -> Box<dyn Layout> {
+        Box::new(Self {
+            inner: self.inner.boxed(),
+            vertical: self.vertical,
+        })
+    }
+}
+
+macro_rules! impl_debug {
+    ($($ty:ident),*) => {
+        $(
+            impl fmt::Debug for $ty {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.debug_struct(stringify!($ty))
+                        .field("inner", &self.inner.name())
+                        .finish_non_exhaustive()
+                }
+            }
+        )*
+    };
+}
+
+impl_debug!(Spacing, SmartBorders, Mirror, Reflect);
+
+/// Applies `f` to the space given to each window, including its
+/// border, and then takes the border back out.
+fn map_cells<F>(ctxt: &LayoutCtxt<'_>, actions: Vec<LayoutAction>, f: F) -> Vec<LayoutAction>
+where
+    F: Fn(Geometry) -> Geometry,
+{
+    // borders set by modifiers further in take precedence
+    let borders: Vec<(XWindowID, u32)> = actions
+        .iter()
+        .filter_map(|a| match a {
+            LayoutAction::SetBorder { id, width } => Some((*id, *width)),
+            _ => None,
+        })
+        .collect();
+    let border_of = |id: XWindowID| {
+        borders
+            .iter()
+            .rev()
+            .find(|(w, _)| *w == id)
+            .map(|(_, width)| *width)
+            .unwrap_or(ctxt.border_px(id))
+    };
+
+    actions
+        .into_iter()
+        .map(|action| match action {
+            LayoutAction::Resize { id, geom } => {
+                let bwidth = border_of(id);
+                let cell = f(expand(geom, bwidth));
+                LayoutAction::Resize { id, geom: shrink(cell, bwidth) }
+            }
+            action => action,
+        })
+        .collect()
+}
+
+/// Moves a cell on the screen `scr` apart from the cells next to it
+/// by the `inner` gap, and away from the edges by the `outer` gap.
+fn space(cell: Geometry, scr: Geometry, inner: u32, outer: u32) -> Geometry {
+    /* shrink every cell by half the inner gap on each side, so
+    each pair of cells ends up a whole gap apart, and move the
+    edges of the screen in so they end up the outer gap away */
+    let (lo, hi) = (inner as i32 / 2, inner as i32 - inner as i32 / 2);
+    let outer = outer as i32;
+    let region = Geometry::new(
+        scr.x + outer - lo,
+        scr.y + outer - lo,
+        scr.height - 2 * outer + inner as i32,
+        scr.width - 2 * outer + inner as i32,
+    );
+
+    let cell = rescale(cell, scr, region);
+    Geometry::new(cell.x + lo, cell.y + lo, cell.height - lo - hi, cell.width - lo - hi)
+}
+
+/// Swaps the axes of a cell on the screen `scr`.
+fn mirror(cell: Geometry, scr: Geometry) -> Geometry {
+    // the screen as it would be with its axes swapped
+    let swapped = Geometry::new(scr.x, scr.y, scr.width, scr.height);
+    let cell = Geometry::new(
+        scr.x + (cell.y - scr.y),
+        scr.y + (cell.x - scr.x),
+        cell.width,
+        cell.height,
+    );
+    rescale(cell, swapped, scr)
+}
+
+/// Flips a cell on the screen `scr` from left to right,
+/// or from top to bottom if `vertical` is set.
+fn reflect(mut cell: Geometry, scr: Geometry, vertical: bool) -> Geometry {
+    if vertical {
+        cell.y = 2 * scr.y + scr.height - (cell.y + cell.height);
+    } else {
+        cell.x = 2 * scr.x + scr.width - (cell.x + cell.width);
+    }
+    cell
+}
+
+/// Grows a window's geometry to include its border.
+fn expand(geom: Geometry, bwidth: u32) -> Geometry {
+    let bwidth = bwidth as i32 * 2;
+    Geometry::new(geom.x, geom.y, geom.height + bwidth, geom.width + bwidth)
+}
+
+/// Takes a window's border back out of the space given to it.
+fn shrink(geom: Geometry, bwidth: u32) -> Geometry {
+    let bwidth = bwidth as i32 * 2;
+    Geometry::new(
+        geom.x,
+        geom.y,
+        (geom.height - bwidth).max(1),
+        (geom.width - bwidth).max(1),
+    )
+}
+
+/// Maps a geometry inside `from` onto the same place inside `to`.
+fn rescale(geom: Geometry, from: Geometry, to: Geometry) -> Geometry {
+    let scale = |pos: i32, from_pos: i32, from_len: i32, to_pos: i32, to_len: i32| {
+        if from_len == 0 {
+            return to_pos
+        }
+        to_pos + ((pos - from_pos) as i64 * to_len as i64 / from_len as i64) as i32
+    };
+    let sx = |x| scale(x, from.x, from.width, to.x, to.width);
+    let sy = |y| scale(y, from.y, from.height, to.y, to.height);
+
+    let (left, right) = (sx(geom.x), sx(geom.x + geom.width));
+    let (top, bottom) = (sy(geom.y), sy(geom.y + geom.height));
+    Geometry::new(left, top, bottom - top, right - left)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::layouts::tests::TestWorkspace;
+    use crate::layouts::Monocle;
+
+    const SCREEN: Geometry = Geometry { x: 0, y: 0, height: 1080, width: 1920 };
+
+    /// Gives each window under layout a column of the screen.
+    #[derive(Debug, Clone)]
+    struct Columns;
+
+    impl Layout for Columns {
+        fn name(&self) -> &str {
+            "Columns"
+        }
+
+        fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+            let ids: Vec<_> = ctxt.workspace.clients_in_layout().map(|c| c.id()).collect();
+            let columns = ctxt.screen.effective_geom().split_vert_n(ids.len());
+            ids.into_iter()
+                .zip(columns)
+                .map(|(id, geom)| LayoutAction::Resize { id, geom: ctxt.fit_border(id, geom) })
+                .collect()
+        }
+
+        fn boxed(&self) -> Box<dyn Layout> {
+            Box::new(self.clone())
+        }
+
+        fn receive_update(&self, _update: &Update) {}
+
+        fn style(&self) -> LayoutType {
+            LayoutType::Tiled
+        }
+    }
+
+    #[test]
+    fn test_smart_borders() {
+        let layout = SmartBorders::new(Columns.boxed());
+
+        // a lone window loses its border and takes up its whole cell
+        let t = TestWorkspace::new(1);
+        let id = t.ids()[0];
+        assert_eq!(
+            layout.layout(t.ctxt()),
+            [
+                LayoutAction::SetBorder { id, width: 0 },
+                LayoutAction::Resize { id, geom: SCREEN },
+            ]
+        );
+
+        // windows that share the screen keep their borders
+        let t = TestWorkspace::new(2);
+        assert_eq!(layout.layout(t.ctxt()), Columns.layout(t.ctxt()));
+
+        // as do windows that take up the same space
+        let mut t = TestWorkspace::new(2);
+        let ids = t.ids();
+        t.focus(ids[0]);
+        let actions = SmartBorders::new(Monocle::new().boxed()).layout(t.ctxt());
+        for id in ids {
+            assert!(actions.contains(&LayoutAction::SetBorder { id, width: 0 }));
+            assert!(actions.contains(&LayoutAction::Resize { id, geom: SCREEN }));
+        }
+    }
+
+    #[test]
+    fn test_spacing_smart_borders() {
+        let layout = Spacing::new(SmartBorders::new(Columns.boxed()).boxed()).with_gaps(10, 10);
+
+        // the border taken away further in is not taken back out again
+        let t = TestWorkspace::new(1);
+        let id = t.ids()[0];
+        assert_eq!(
+            layout.layout(t.ctxt()),
+            [
+                LayoutAction::SetBorder { id, width: 0 },
+                LayoutAction::Resize { id, geom: Geometry::new(10, 10, 1060, 1900) },
+            ]
+        );
+
+        let t = TestWorkspace::new(2);
+        let ids = t.ids();
+        let bw = t.config.border_px as i32 * 2;
+        let (h, w) = (1060 - bw, 945 - bw);
+        assert_eq!(
+            layout.layout(t.ctxt()),
+            [
+                LayoutAction::Resize { id: ids[0], geom: Geometry::new(10, 10, h, w) },
+                LayoutAction::Resize { id: ids[1], geom: Geometry::new(965, 10, h, w) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rescale() {
+        let half = Geometry::new(960, 0, 540, 960);
+        assert_eq!(rescale(half, SCREEN, SCREEN), half);
+
+        let to = Geometry::new(100, 100, 540, 960);
+        assert_eq!(rescale(half, SCREEN, to), Geometry::new(580, 100, 270, 480));
+        assert_eq!(rescale(SCREEN, SCREEN, to), to);
+
+        // an empty space maps everything onto its corner
+        let empty = Geometry::new(10, 20, 0, 0);
+        assert_eq!(rescale(half, empty, to), Geometry::new(100, 100, 0, 0));
+    }
+
+    #[test]
+    fn test_expand_shrink() {
+        let geom = Geometry::new(10, 10, 100, 200);
+        assert_eq!(expand(geom, 2), Geometry::new(10, 10, 104, 204));
+        assert_eq!(shrink(expand(geom, 2), 2), geom);
+
+        // windows never shrink away entirely
+        assert_eq!(shrink(geom, 60), Geometry::new(10, 10, 1, 80));
+    }
+
+    #[test]
+    fn test_space() {
+        let (left, right) = SCREEN.split_vert_ratio(0.5);
+        assert_eq!(space(left, SCREEN, 10, 10), Geometry::new(10, 10, 1060, 945));
+        assert_eq!(space(right, SCREEN, 10, 10), Geometry::new(965, 10, 1060, 945));
+
+        // odd gaps are split as evenly as they can be
+        assert_eq!(space(left, SCREEN, 5, 0), Geometry::new(0, 0, 1080, 957));
+        assert_eq!(space(right, SCREEN, 5, 0), Geometry::new(962, 0, 1080, 958));
+    }
+
+    #[test]
+    fn test_mirror() {
+        let (left, right) = SCREEN.split_vert_ratio(0.25);
+        assert_eq!(mirror(left, SCREEN), Geometry::new(0, 0, 270, 1920));
+        assert_eq!(mirror(right, SCREEN), Geometry::new(0, 270, 810, 1920));
+        assert_eq!(mirror(mirror(left, SCREEN), SCREEN), left);
+    }
+
+    #[test]
+    fn test_reflect() {
+        let cell = Geometry::new(0, 0, 270, 480);
+        assert_eq!(reflect(cell, SCREEN, false), Geometry::new(1440, 0, 270, 480));
+        assert_eq!(reflect(cell, SCREEN, true), Geometry::new(0, 810, 270, 480));
+
+        let offset = Geometry::new(1920, 0, 1080, 1920);
+        let cell = Geometry::new(1920, 0, 1080, 480);
+        assert_eq!(reflect(cell, offset, false), Geometry::new(3360, 0, 1080, 480));
+    }
+}
@@ -0,0 +1,207 @@
+use std::cell::Cell;
+
+use super::{
+    update::{CycleFocused, Update},
+    Layout, LayoutAction, LayoutCtxt, LayoutType,
+};
+
+use crate::core::Workspace;
+use crate::types::Direction;
+use crate::backend::x::XWindowID;
+
+/// A layout that shows one window at a time, taking up the whole screen.
+///
+/// Every window under layout is given the whole screen, and the shown
+/// window is stacked above the rest. The shown window follows the focus,
+/// and can also be cycled with [`CycleFocused`], which focuses the newly
+/// shown window.
+///
+/// Its display name shows the position of the shown window and the
+/// number of windows, such as "Monocle [2/5]".
+#[derive(Debug, Clone, Default)]
+pub struct Monocle {
+    // the window last shown
+    shown: Cell<Option<XWindowID>>,
+    // the direction to cycle in on the next layout, if any
+    cycle: Cell<Option<Direction>>,
+}
+
+impl Monocle {
+    /// Creates a new Monocle layout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Layout for Monocle {
+    fn name(&self) -> &str {
+        "Monocle"
+    }
+
+    fn display_name(&self, ws: &Workspace) -> String {
+        let ids = Self::ids(ws);
+        match self.current(ws, &ids) {
+            Some(shown) => {
+                let idx = ids.iter().position(|id| *id == shown).unwrap();
+                format!("Monocle [{}/{}]", idx + 1, ids.len())
+            }
+            None => "Monocle".into(),
+        }
+    }
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        self._layout(ctxt)
+    }
+
+    fn boxed(&self) -> Box<dyn Layout> {
+        Box::new(self.clone())
+    }
+
+    fn receive_update(&self, update: &Update) {
+        if let Some(CycleFocused(dir)) = update.as_update() {
+            self.cycle.set(Some(*dir));
+        }
+    }
+
+    fn style(&self) -> LayoutType {
+        LayoutType::Tiled
+    }
+}
+
+#[doc(hidden)]
+impl Monocle {
+    fn _layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let ws = ctxt.workspace;
+        let ids = Self::ids(ws);
+        let cycle = self.cycle.take();
+
+        let Some(current) = self.current(ws, &ids) else {
+            self.shown.set(None);
+            return Vec::new()
+        };
+        let shown = match cycle {
+            Some(dir) => {
+                let idx = ids.iter().position(|id| *id == current).unwrap();
+                let next = match dir {
+                    Direction::Forward => (idx + 1) % ids.len(),
+                    Direction::Backward => (idx + ids.len() - 1) % ids.len(),
+                };
+                ids[next]
+            }
+            None => current,
+        };
+        self.shown.set(Some(shown));
+
+        let geom = ctxt.screen.effective_geom();
+        let mut ret: Vec<_> = ids
+            .into_iter()
+            .map(|id| LayoutAction::Resize { id, geom: ctxt.fit_border(id, geom) })
+            .collect();
+        ret.push(LayoutAction::StackOnTop(shown));
+        if cycle.is_some() {
+            ret.push(LayoutAction::Focus(shown));
+        }
+        ret
+    }
+
+    fn ids(ws: &Workspace) -> Vec<XWindowID> {
+        ws.clients_in_layout().map(|c| c.id()).collect()
+    }
+
+    /// The window that should be shown, before any cycling.
+    ///
+    /// This is the focused window if it is under layout, and
+    /// otherwise the window last shown.
+    fn current(&self, ws: &Workspace, ids: &[XWindowID]) -> Option<XWindowID> {
+        let focused = ws.focused_client().map(|c| c.id());
+        focused
+            .filter(|id| ids.contains(id))
+            .or(self.shown.get().filter(|id| ids.contains(id)))
+            .or(ids.first().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::layouts::tests::TestWorkspace;
+    use crate::layouts::update::IntoUpdate;
+    use crate::types::Geometry;
+
+    /// Cycles `layout` and focuses the window it shows, as the workspace would.
+    fn cycle(layout: &Monocle, t: &mut TestWorkspace, dir: Direction) -> XWindowID {
+        layout.receive_update(&CycleFocused(dir).into_update());
+        let actions = layout.layout(t.ctxt());
+        let Some(&LayoutAction::Focus(shown)) = actions.last() else {
+            panic!("cycling did not focus a window: {:?}", actions)
+        };
+        assert!(actions.contains(&LayoutAction::StackOnTop(shown)));
+        t.focus(shown);
+        shown
+    }
+
+    #[test]
+    fn test_monocle_layout() {
+        let mut t = TestWorkspace::new(3);
+        let ids = t.ids();
+        t.focus(ids[1]);
+
+        // every window gets the whole screen, with the focused one on top
+        let layout = Monocle::new();
+        let full = t.ctxt().fit_border(ids[0], Geometry::new(0, 0, 1080, 1920));
+        let mut expected: Vec<_> = ids
+            .iter()
+            .map(|&id| LayoutAction::Resize { id, geom: full })
+            .collect();
+        expected.push(LayoutAction::StackOnTop(ids[1]));
+        assert_eq!(layout.layout(t.ctxt()), expected);
+    }
+
+    #[test]
+    fn test_monocle_cycle() {
+        let mut t = TestWorkspace::new(3);
+        let ids = t.ids();
+        t.focus(ids[0]);
+        let layout = Monocle::new();
+
+        // cycling wraps around at both ends
+        assert_eq!(cycle(&layout, &mut t, Direction::Backward), ids[2]);
+        assert_eq!(cycle(&layout, &mut t, Direction::Backward), ids[1]);
+        assert_eq!(cycle(&layout, &mut t, Direction::Forward), ids[2]);
+        assert_eq!(cycle(&layout, &mut t, Direction::Forward), ids[0]);
+
+        // the cycle only applies to the next layout
+        assert_eq!(layout.layout(t.ctxt()).last(), Some(&LayoutAction::StackOnTop(ids[0])));
+    }
+
+    #[test]
+    fn test_monocle_floating_focus() {
+        let mut t = TestWorkspace::new(2);
+        let ids = t.ids();
+        t.focus(ids[0]);
+        let layout = Monocle::new();
+        assert_eq!(cycle(&layout, &mut t, Direction::Forward), ids[1]);
+
+        // a focused floating window leaves the last shown window in place
+        let floater = t.add(true);
+        let actions = layout.layout(t.ctxt());
+        assert_eq!(actions.last(), Some(&LayoutAction::StackOnTop(ids[1])));
+        let resized = |a: &LayoutAction| matches!(a, LayoutAction::Resize { id, .. } if *id == floater);
+        assert!(!actions.iter().any(resized));
+        assert_eq!(layout.display_name(&t.ws), "Monocle [2/2]");
+    }
+
+    #[test]
+    fn test_monocle_display_name() {
+        let layout = Monocle::new();
+        assert_eq!(layout.display_name(&TestWorkspace::new(0).ws), "Monocle");
+
+        let mut t = TestWorkspace::new(5);
+        let ids = t.ids();
+        t.focus(ids[1]);
+        assert_eq!(layout.display_name(&t.ws), "Monocle [2/5]");
+        t.focus(ids[4]);
+        assert_eq!(layout.display_name(&t.ws), "Monocle [5/5]");
+    }
+}
@@ -0,0 +1,142 @@
+use std::cell::Cell;
+
+use super::{
+    update::{ResizeMain, Update},
+    Layout, LayoutAction, LayoutCtxt, LayoutType,
+};
+
+use crate::types::Geometry;
+use crate::backend::x::XWindowID;
+
+/// A layout with the main window in a column in the middle,
+/// and stacks on both sides of it.
+///
+/// The main window is the first window under layout. The stack on
+/// the left holds the first half of the other windows, and the one
+/// on the right holds the rest. With only two windows, the main
+/// window is on the left, as with [`DynamicTiled`](super::DynamicTiled).
+///
+/// The width of the middle column can be changed with [`ResizeMain`].
+#[derive(Debug, Clone)]
+pub struct ThreeColumn {
+    // the proportion of the width that the middle column takes up
+    ratio: Cell<f32>,
+}
+
+impl ThreeColumn {
+    /// Creates a new ThreeColumn layout, with the given ratio
+    /// of the width taken up by the middle column.
+    pub fn new(ratio: f32) -> Self {
+        Self {
+            ratio: Cell::new(ratio),
+        }
+    }
+}
+
+impl Default for ThreeColumn {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+impl Layout for ThreeColumn {
+    fn name(&self) -> &str {
+        "ThreeCol"
+    }
+
+    fn layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        self._layout(ctxt)
+    }
+
+    fn boxed(&self) -> Box<dyn Layout> {
+        Box::new(self.clone())
+    }
+
+    fn receive_update(&self, update: &Update) {
+        if let Some(ResizeMain(inc)) = update.as_update() {
+            self.ratio.set((self.ratio.get() + inc).clamp(0.1, 0.9));
+        }
+    }
+
+    fn style(&self) -> LayoutType {
+        LayoutType::Tiled
+    }
+
+    fn save_params(&self) -> Option<String> {
+        Some(self.ratio.get().to_string())
+    }
+
+    fn restore_params(&self, params: &str) {
+        if let Ok(ratio) = params.parse() {
+            self.ratio.set(ratio);
+        }
+    }
+}
+
+#[doc(hidden)]
+impl ThreeColumn {
+    fn _layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let ids: Vec<XWindowID> = ctxt.workspace.clients_in_layout().map(|c| c.id()).collect();
+        let geoms = Self::columns(ctxt.screen.effective_geom(), self.ratio.get(), ids.len());
+
+        ids.into_iter()
+            .zip(geoms)
+            .map(|(id, geom)| LayoutAction::Resize { id, geom: ctxt.fit_border(id, geom) })
+            .collect()
+    }
+
+    /// Lays `n` windows out over `geom`, with the main window first.
+    fn columns(geom: Geometry, ratio: f32, n: usize) -> Vec<Geometry> {
+        match n {
+            0 => Vec::new(),
+            1 => vec![geom],
+            2 => {
+                let (main, stack) = geom.split_vert_ratio(ratio);
+                vec![main, stack]
+            }
+            n => {
+                // the sides split what is left over by the middle
+                let (left, rest) = geom.split_vert_ratio((1.0 - ratio) / 2.0);
+                let (main, right) = rest.split_vert_ratio(2.0 * ratio / (1.0 + ratio));
+                let nleft = (n - 1).div_ceil(2);
+
+                let mut geoms = vec![main];
+                geoms.extend(left.split_horz_n(nleft));
+                geoms.extend(right.split_horz_n(n - 1 - nleft));
+                geoms
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Geometry = Geometry { x: 0, y: 0, height: 1080, width: 1920 };
+
+    #[test]
+    fn test_three_columns() {
+        assert!(ThreeColumn::columns(SCREEN, 0.5, 0).is_empty());
+        assert_eq!(ThreeColumn::columns(SCREEN, 0.5, 1), [SCREEN]);
+        assert_eq!(
+            ThreeColumn::columns(SCREEN, 0.5, 2),
+            [Geometry::new(0, 0, 1080, 960), Geometry::new(960, 0, 1080, 960)]
+        );
+
+        // the left stack takes the odd window out
+        assert_eq!(
+            ThreeColumn::columns(SCREEN, 0.5, 4),
+            [
+                Geometry::new(480, 0, 1080, 960),
+                Geometry::new(0, 0, 540, 480),
+                Geometry::new(0, 540, 540, 480),
+                Geometry::new(1440, 0, 1080, 480),
+            ]
+        );
+        assert_eq!(
+            ThreeColumn::columns(SCREEN, 0.25, 3)[..2],
+            [Geometry::new(720, 0, 1080, 480), Geometry::new(0, 0, 1080, 720)]
+        );
+    }
+}
@@ -3,24 +3,32 @@ use std::cell::Cell;
 use tracing::debug;
 
 use super::{
-    update::{ResizeMain, Update, UpdateBorderPx},
+    update::{DecMain, IncMain, ResizeMain, Update, UpdateBorderPx},
     Layout, LayoutAction, LayoutCtxt, LayoutType,
 };
 
-use crate::core::Workspace;
-use crate::types::{Cardinal, Geometry};
-use crate::backend::x::XWindowID;
+use crate::backend::x::{XWindowID, Xid};
 
-/// A simple dynamic tiling layout, with a main window
+/// A simple dynamic tiling layout, with a main area
 /// and a stack on the side.
+///
+/// The main area holds one window by default, and its size can be
+/// changed with the [`IncMain`] and [`DecMain`] updates. The main
+/// window always comes first in the main area, followed by the
+/// other windows in the order they are in the workspace.
+/// Each workspace keeps its own ratio and main count.
 #[derive(Debug, Clone)]
 pub struct DynamicTiled {
-    // the proportion of space that the main window should take up
+    // the proportion of space that the main area should take up
     ratio: Cell<f32>,
-    // the border width set by the user.
+    // the number of windows in the main area
+    nmain: Cell<u32>,
+    // the border width of windows that do not set their own
     bwidth: Cell<u32>,
     // the ID of the main window, if set
     main: Cell<Option<XWindowID>>,
+    // whether window sizes are constrained by their size hints
+    size_hints: bool,
 }
 
 impl DynamicTiled {
@@ -29,10 +37,34 @@ impl DynamicTiled {
     pub fn new(ratio: f32, bwidth: u32) -> Self {
         Self {
             ratio: Cell::new(ratio),
+            nmain: Cell::new(1),
             bwidth: Cell::new(bwidth),
             main: Cell::new(None),
+            size_hints: false,
         }
     }
+
+    /// Sets the number of windows in the main area.
+    ///
+    /// This is 1 by default.
+    pub fn with_main_count(self, nmain: u32) -> Self {
+        self.nmain.set(nmain);
+        self
+    }
+
+    /// Returns the number of windows in the main area.
+    pub fn main_count(&self) -> u32 {
+        self.nmain.get()
+    }
+
+    /// Sets whether windows are sized according to their size hints,
+    /// instead of filling the space given to them.
+    ///
+    /// This is off by default.
+    pub fn with_size_hints(mut self, honour: bool) -> Self {
+        self.size_hints = honour;
+        self
+    }
 }
 
 impl Layout for DynamicTiled {
@@ -51,6 +83,10 @@ impl Layout for DynamicTiled {
     fn receive_update(&self, update: &Update) {
         if let Some(ResizeMain(inc)) = update.as_update() {
             self.ratio.set(self.ratio.get() + inc);
+        } else if let Some(IncMain(inc)) = update.as_update() {
+            self.nmain.set(self.nmain.get().saturating_add(*inc));
+        } else if let Some(DecMain(dec)) = update.as_update() {
+            self.nmain.set(self.nmain.get().saturating_sub(*dec));
         } else if let Some(UpdateBorderPx(new)) = update.as_update() {
             self.bwidth.set(*new);
         }
@@ -59,17 +95,41 @@ impl Layout for DynamicTiled {
     fn style(&self) -> super::LayoutType {
         LayoutType::Tiled
     }
+
+    fn honours_size_hints(&self) -> bool {
+        self.size_hints
+    }
+
+    fn save_params(&self) -> Option<String> {
+        let main = self.main.get().map(|id| id.val().to_string());
+        Some(format!(
+            "{} {} {}",
+            self.ratio.get(),
+            main.as_deref().unwrap_or("-"),
+            self.nmain.get()
+        ))
+    }
+
+    fn restore_params(&self, params: &str) {
+        let mut params = params.split(' ');
+        if let Some(ratio) = params.next().and_then(|r| r.parse().ok()) {
+            self.ratio.set(ratio);
+        }
+        self.main.set(params.next().and_then(|m| m.parse().ok()).map(Xid));
+        if let Some(nmain) = params.next().and_then(|n| n.parse().ok()) {
+            self.nmain.set(nmain);
+        }
+    }
 }
 
 #[doc(hidden)]
 impl DynamicTiled {
     fn _layout(&self, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
-        let geom = ctxt.screen.effective_geom();
         let ws = ctxt.workspace;
 
         /* we have a main window */
         if let Some(main_id) = self.main.get() {
-            self._layout_with_main(main_id, geom, ws)
+            self._layout_with_main(main_id, ctxt)
         } else {
             // we have no main
             if ws.managed_count() == 0 {
@@ -90,20 +150,10 @@ impl DynamicTiled {
         }
     }
 
-    fn _layout_with_main(
-        &self,
-        main_id: XWindowID,
-        geom: Geometry,
-        ws: &Workspace,
-    ) -> Vec<LayoutAction> {
-        use Cardinal::*;
-
-        let bwidth = self.bwidth.get() as i32;
-
-        /* weird ass bodge because of X server shenaniganery:
-        we have to trim off double the bwidth because of how
-        the X server counts window borders.*/
-        let usable_geom = geom.trim(bwidth * 2, Right).trim(bwidth * 2, Down);
+    fn _layout_with_main(&self, main_id: XWindowID, ctxt: LayoutCtxt<'_>) -> Vec<LayoutAction> {
+        let geom = ctxt.screen.effective_geom();
+        let ws = ctxt.workspace;
+        let fit = |id, geom| ctxt.fit_border_or(id, geom, self.bwidth.get());
 
         if ws.managed_count() == 0 {
             /* managed count is 0 but we have a main,
@@ -136,51 +186,76 @@ impl DynamicTiled {
             /* we only have a main window */
             debug!("Only main exists, tiling to full window");
 
-            debug!("new window geom: {:?}", usable_geom);
+            let geom = fit(current_main, geom);
+            debug!("new window geom: {:?}", geom);
             vec![LayoutAction::Resize {
                 id: current_main,
-                geom: usable_geom,
+                geom,
             }]
         } else {
             // managed count > 1
             debug_assert!(ws.managed_count() > 1);
             debug!("Multiple windows mapped, recalculating");
 
-            let (main, sec) = usable_geom.split_vert_ratio(self.ratio.get());
+            // the main window goes first, followed by the rest in order
+            let ids: Vec<XWindowID> = std::iter::once(current_main)
+                .chain(
+                    ws.clients_in_layout()
+                        .map(|c| c.id())
+                        .filter(|id| *id != current_main),
+                )
+                .collect();
+            let main_count = (self.nmain.get() as usize).min(ids.len());
 
-            // do standard division and round up to nearest integer
-            /* this ensures that if bwidth is odd, we always round up
-            while keeping it unaffected if bwidth is even */
-            let half_bwidth = (bwidth as f32 / 2.0).ceil() as i32;
+            /* if every window is in either the main area or the stack,
+            they share the whole screen in a single column */
+            if main_count == 0 || main_count == ids.len() {
+                let geoms = geom.split_horz_n(ids.len());
+                return ids
+                    .into_iter()
+                    .zip(geoms)
+                    .map(|(id, geom)| LayoutAction::Resize { id, geom: fit(id, geom) })
+                    .collect();
+            }
 
-            //let odd_bwidth = bwidth % 2 != 0;
+            let (main, sec) = geom.split_vert_ratio(self.ratio.get());
 
-            let mut ret = vec![LayoutAction::Resize {
-                id: current_main,
-                geom: main.trim(half_bwidth, Right),
-            }];
+            let main_geoms = main.split_horz_n(main_count);
+            let sec_geoms = sec.split_horz_n(ids.len() - main_count);
 
-            // get no of secondary windows
-            let sec_count = if ws.managed_count() == 0 {
-                unreachable!()
-            } else {
-                ws.managed_count() - 1
-            };
-
-            //todo: account for border width
-            let sec_geoms = sec.split_horz_n(sec_count);
-
-            ws.clients_in_layout()
-                .filter(|c| c.id() != current_main)
-                .enumerate()
-                .for_each(|(i, c)| {
-                    ret.push(LayoutAction::Resize {
-                        id: c.id(),
-                        geom: sec_geoms[i],
-                    })
-                });
-
-            ret
+            ids.into_iter()
+                .zip(main_geoms.into_iter().chain(sec_geoms))
+                .map(|(id, geom)| LayoutAction::Resize { id, geom: fit(id, geom) })
+                .collect()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::layouts::tests::TestWorkspace;
+    use crate::layouts::update::IntoUpdate;
+    use crate::types::Geometry;
+
+    #[test]
+    fn test_tiled_borders() {
+        let mut t = TestWorkspace::new(2);
+        let ids = t.ids();
+        let layout = DynamicTiled::new(0.5, 2);
+        let geom_of = |t: &TestWorkspace, target| {
+            layout.layout(t.ctxt()).into_iter().find_map(|a| match a {
+                LayoutAction::Resize { id, geom } if id == target => Some(geom),
+                _ => None,
+            })
+        };
+        assert_eq!(geom_of(&t, ids[0]), Some(Geometry::new(0, 0, 1076, 956)));
+
+        // the border width is the one last sent, unless a window sets its own
+        layout.receive_update(&UpdateBorderPx(5).into_update());
+        t.ws.windows.lookup_mut(ids[1]).unwrap().set_border_px(Some(0));
+        assert_eq!(geom_of(&t, ids[0]), Some(Geometry::new(0, 0, 1070, 950)));
+        assert_eq!(geom_of(&t, ids[1]), Some(Geometry::new(960, 0, 1080, 960)));
+    }
+}
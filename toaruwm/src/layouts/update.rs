@@ -1,6 +1,6 @@
 use std::any::Any;
 
-use crate::types::Direction;
+use crate::types::{Cardinal, Direction};
 
 /// A dynamically typed object that can change the behavior of a layout.
 pub struct Update(Box<dyn Any>);
@@ -41,25 +41,71 @@ where
 pub struct ResizeMain(pub f32);
 impl IntoUpdate for ResizeMain {}
 
+/// Increase the number of windows in the main area of the layout
+/// by the given amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncMain(pub u32);
+impl IntoUpdate for IncMain {}
+
+/// Decrease the number of windows in the main area of the layout
+/// by the given amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecMain(pub u32);
+impl IntoUpdate for DecMain {}
+
+/// Increase the number of columns of the layout by the given amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncColumns(pub u32);
+impl IntoUpdate for IncColumns {}
+
+/// Decrease the number of columns of the layout by the given amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecColumns(pub u32);
+impl IntoUpdate for DecColumns {}
+
 /// Update the internally stored border thickness.
 ///
-/// Some tiling layouts may have to account for the user-set
-/// border thickness when calculating window geometries.
-/// This update tells them to change their internal store
-/// of border thickness so they can update their calculations
-/// accordingly.
+/// Tiling layouts such as [`DynamicTiled`](super::DynamicTiled)
+/// make room for the border of each window when calculating window
+/// geometries. This update sets the border thickness they use for
+/// windows that do not set their own with a window rule.
+///
+/// The configured border thickness is sent to every layout when
+/// the config is built.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct UpdateBorderPx(pub u32);
 impl IntoUpdate for UpdateBorderPx {}
 
 /// Cycle the shown window of the layout in the given direction.
 ///
-/// Some layouts (like a monocle layout) might keep only one window
-/// mapped at time, taking up the entire screen, while the rest
-/// of the windows are unmapped.
+/// Some layouts (like [`Monocle`](super::Monocle)) might show only
+/// one window at a time, taking up the entire screen, while the rest
+/// of the windows are hidden.
 ///
 /// Sending this message to the layout tells it to cycle to the next
 /// window to focus to.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct CycleFocused(pub Direction);
 impl IntoUpdate for CycleFocused {}
+
+/// Rotate the split holding the focused window, so that it divides
+/// its space the other way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotate;
+impl IntoUpdate for Rotate {}
+
+/// Swap the two sides of the split holding the focused window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flip;
+impl IntoUpdate for Flip {}
+
+/// Change the ratio of the split holding the focused window
+/// by the given increment/decrement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResizeSplit(pub f32);
+impl IntoUpdate for ResizeSplit {}
+
+/// Insert the next window on the given side of the focused window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsertSide(pub Cardinal);
+impl IntoUpdate for InsertSide {}
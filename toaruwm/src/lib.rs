@@ -253,6 +253,10 @@ pub enum ToaruError {
     #[error("Unknown workspace {0}")]
     UnknownWorkspace(String),
 
+    /// A request to show or hide a scratchpad unknown to ToaruWM.
+    #[error("Unknown scratchpad {0}")]
+    UnknownScratchpad(String),
+
     /// An invalid point on the root window.
     #[error("Invalid point ({0}, {1})")]
     InvalidPoint(i32, i32),
@@ -265,6 +269,10 @@ pub enum ToaruError {
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
 
+    /// An error while restarting the window manager in-place.
+    #[error("Could not restart: {0}")]
+    Restart(String),
+
     /// An error not covered by ToaruWM.
     #[error("Error: {0}")]
     OtherError(String),
@@ -310,6 +318,10 @@ macro_rules! toaruerr {
     (invalidcfg: $t:expr) => {
         ToaruError::InvalidConfig($t)
     };
+    // Restart
+    (restart: $t:expr) => {
+        ToaruError::Restart($t)
+    };
     // OtherError
     (other: $t:expr) => {
         ToaruError::OtherError($t)
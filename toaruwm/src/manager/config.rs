@@ -9,13 +9,16 @@ use custom_debug_derive::Debug;
 
 use std::any::Any;
 use std::collections::HashMap;
+use std::time::Duration;
 
-use crate::core::WorkspaceSpec;
+use crate::core::{ScratchpadSpec, WorkspaceSpec};
 use crate::layouts::{
+    modifiers::Spacing,
     update::{IntoUpdate, UpdateBorderPx},
     DynamicTiled, Floating, Layout,
 };
-use crate::manager::state::{RuntimeConfig, WmConfig};
+use crate::manager::rules::WindowRule;
+use crate::manager::state::{ActivationPolicy, RuntimeConfig, WmConfig};
 use crate::types::Color;
 use crate::{Result, ToaruError::*};
 
@@ -53,10 +56,14 @@ use crate::{Result, ToaruError::*};
 ///
 /// - *Float Classes*: the set of window classes that the window
 /// manager will not place under layout.
+/// - *Rules*: the [`WindowRule`]s applied to windows as they are mapped.
+/// - *Scratchpads*: the named scratchpads that can be toggled.
 /// - *Border Pixel*: The thickness of the window border.
 /// - *Window Gaps*: The gaps between windows.
 /// - *Focus Follows Pointer* Whether the focused window should follow the
 /// pointer's position on the screen.
+/// - *Activation Policy*: Whether clients asking to be activated
+/// are focused or marked as urgent.
 /// - *Unfocused*: The border color of unfocused windows.
 /// - *Focused*: The border color of focused windows.
 /// - *Urgent*: The border color of focused windows.
@@ -126,12 +133,20 @@ pub struct ToaruConfig {
     pub(crate) layouts: Vec<Box<dyn Layout>>,
     /// The window classes that should float.
     pub(crate) float_classes: Vec<String>,
+    /// The rules applied to windows as they are mapped.
+    pub(crate) rules: Vec<WindowRule>,
+    /// The named scratchpads that can be toggled.
+    pub(crate) scratchpads: Vec<ScratchpadSpec>,
     /// The width of the window border.
     pub(crate) border_px: u32,
     /// The gap between windows.
     pub(crate) window_gap: u32,
     /// Whether or not the window focus should follow the pointer.
     pub(crate) focus_follows_ptr: bool,
+    /// How to respond to clients asking to be activated.
+    pub(crate) activation_policy: ActivationPolicy,
+    /// How long to wait for a client to close before killing it.
+    pub(crate) close_timeout: Duration,
     /// The color to apply to the borders of an unfocused window.
     pub(crate) unfocused: Color,
     /// The color to apply to the borders of a focused window.
@@ -262,11 +277,31 @@ impl ToaruConfig {
         &self.float_classes
     }
 
+    /// The rules applied to windows as they are mapped.
+    pub fn rules(&self) -> &[WindowRule] {
+        &self.rules
+    }
+
+    /// The named scratchpads that can be toggled.
+    pub fn scratchpads(&self) -> &[ScratchpadSpec] {
+        &self.scratchpads
+    }
+
     /// The thickness of the window borders, in pixels.
     pub fn border_px(&self) -> u32 {
         self.border_px
     }
 
+    /// How clients asking to be activated are handled.
+    pub fn activation_policy(&self) -> ActivationPolicy {
+        self.activation_policy
+    }
+
+    /// How long clients asked to close are given before being killed.
+    pub fn close_timeout(&self) -> Duration {
+        self.close_timeout
+    }
+
     /// The border color of unfocused windows.
     pub fn unfocused(&self) -> Color {
         self.unfocused
@@ -314,9 +349,13 @@ impl Config for ToaruConfig {
     fn into_runtime_config(self) -> Self::Runtime {
         WmConfig {
             float_classes: self.float_classes,
+            rules: self.rules,
+            scratchpads: self.scratchpads,
             border_px: self.border_px,
             window_gap: self.window_gap,
             focus_follows_ptr: self.focus_follows_ptr,
+            activation_policy: self.activation_policy,
+            close_timeout: self.close_timeout,
             unfocused: self.unfocused,
             focused: self.focused,
             urgent: self.urgent,
@@ -335,13 +374,17 @@ impl Default for ToaruConfig {
                 WorkspaceSpec::new("3", 0, layouts),
             ],
             layouts: vec![
-                Box::new(DynamicTiled::new(0.5, 2)) as Box<dyn Layout>,
+                Box::new(Spacing::new(DynamicTiled::new(0.5, 2).boxed())) as Box<dyn Layout>,
                 Box::new(Floating::new()) as Box<dyn Layout>,
             ],
             float_classes: Vec::new(),
+            rules: Vec::new(),
+            scratchpads: Vec::new(),
             border_px: 2,
             window_gap: 0,
             focus_follows_ptr: true,
+            activation_policy: ActivationPolicy::default(),
+            close_timeout: Duration::from_secs(5),
             unfocused: Color::from(0x555555),
             focused: Color::from(0xdddddd),
             urgent: Color::from(0xee0000),
@@ -397,6 +440,26 @@ impl ToaruConfigBuilder {
         self
     }
 
+    /// Sets the rules applied to windows as they are mapped.
+    ///
+    /// Rules are applied in the order given.
+    pub fn rules<R>(mut self, rules: R) -> Self
+    where
+        R: IntoIterator<Item = WindowRule>,
+    {
+        self.inner.rules = rules.into_iter().collect();
+        self
+    }
+
+    /// Sets the named scratchpads that can be toggled.
+    pub fn scratchpads<S>(mut self, scratchpads: S) -> Self
+    where
+        S: IntoIterator<Item = ScratchpadSpec>,
+    {
+        self.inner.scratchpads = scratchpads.into_iter().collect();
+        self
+    }
+
     /// Sets the border thickness, in pixels.
     pub fn border_px(mut self, border_px: u32) -> Self {
         self.inner.border_px = border_px;
@@ -415,6 +478,18 @@ impl ToaruConfigBuilder {
         self
     }
 
+    /// Sets how clients asking to be activated are handled.
+    pub fn activation_policy(mut self, activation_policy: ActivationPolicy) -> Self {
+        self.inner.activation_policy = activation_policy;
+        self
+    }
+
+    /// Sets how long clients asked to close are given before being killed.
+    pub fn close_timeout(mut self, close_timeout: Duration) -> Self {
+        self.inner.close_timeout = close_timeout;
+        self
+    }
+
     /// Sets the border color of unfocused windows.
     pub fn unfocused(mut self, unfocused: Color) -> Self {
         self.inner.unfocused = unfocused;
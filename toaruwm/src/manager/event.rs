@@ -32,6 +32,10 @@ pub enum EventAction {
     SetFocusedScreen(Option<Point>),
     /// Destroy the specified client.
     DestroyClient(XWindowID),
+    /// Update the space reserved by the specified unmanaged client.
+    ClientStrutChange(XWindowID),
+    /// Re-read the size hints of the specified client.
+    ClientSizeHintsChange(XWindowID),
     /// Map the specified client and track it internally.
     ///
     /// Applies to normal top-level windows.
@@ -44,16 +48,25 @@ pub enum EventAction {
     ConfigureClient(ConfigureRequestData),
     /// Send the client to the specified workspace.
     ClientToWorkspace(XWindowID, usize),
+    /// Go to the workspace at the specified index.
+    GotoWorkspace(usize),
     /// Run the specified keybind.
     RunKeybind(Keybind, XWindowID),
     /// Run the specified mousebind.
     RunMousebind(Mousebind, XWindowID, Point),
+    /// Activate the specified client at its own request.
+    ///
+    /// The flag is set if the request came from a pager
+    /// on behalf of the user.
+    ActivateClient(XWindowID, bool),
     /// Toggle the client in or out of fullscreen.
     ///
     /// Also toggles _NET_WM_STATE_FULLSCREEN.
     ToggleClientFullscreen(XWindowID, bool),
-    /// Set the state of a window to urgent.
-    ToggleUrgency(XWindowID),
+    /// Set or clear the urgent state of a window.
+    ///
+    /// Also sets or clears _NET_WM_STATE_DEMANDS_ATTENTION.
+    SetUrgency(XWindowID, bool),
     /// Handle an error caused by a certain X event.
     HandleError(XError, XEvent),
 }
@@ -106,7 +119,7 @@ impl EventAction {
             EnterNotify(ev, grab) => {
                 debug!(target: "manager::event","enter notify for window {}; grab: {}", ev.id, grab);
                 if !grab && state.is_managing(ev.id) {
-                    process_enter_notify(ev, state)
+                    process_enter_notify(ev)
                 } else {
                     None
                 }
@@ -178,25 +191,11 @@ fn process_map_request<X: XConn, C: RuntimeConfig>(
     Some(vec![MapTrackedClient(id)])
 }
 
-fn process_enter_notify<X: XConn, C: RuntimeConfig>(
-    ptrev: PointerEvent,
-    state: WmState<'_, X, C>,
-) -> Option<Vec<EventAction>> {
+fn process_enter_notify(ptrev: PointerEvent) -> Option<Vec<EventAction>> {
     use EventAction::*;
 
-    let mut actions = vec![MoveClientFocus(ptrev.id), SetFocusedScreen(Some(ptrev.abs))];
-
-    if let Some(_focused) = state.desktop.current_client() {
-        //fixme: is this necessary?
-        // if next client is set to urgent, unset its urgent flag
-        if let Some(c) = state.lookup_client(ptrev.id) {
-            if c.is_urgent() {
-                actions.push(ToggleUrgency(ptrev.id));
-            }
-        }
-    }
-
-    Some(actions)
+    // the urgent flag is cleared when the client is focused
+    Some(vec![MoveClientFocus(ptrev.id), SetFocusedScreen(Some(ptrev.abs))])
 }
 
 fn process_property_notify<X: XConn, C: RuntimeConfig>(
@@ -213,6 +212,15 @@ fn process_property_notify<X: XConn, C: RuntimeConfig>(
 
     let hints = Atom::WmHints.as_ref();
 
+    let is_strut = atom == Atom::NetWmStrut.as_ref() || atom == Atom::NetWmStrutPartial.as_ref();
+    if is_strut && !state.is_managing(event.id) {
+        return Some(vec![ClientStrutChange(event.id)]);
+    }
+
+    if atom == Atom::WmNormalHints.as_ref() && state.is_managing(event.id) {
+        return Some(vec![ClientSizeHintsChange(event.id)]);
+    }
+
     if !event.deleted && atom == hints {
         let wmhints = if let Ok(Some(h)) = state.conn.get_property(hints, event.id) {
             h
@@ -221,9 +229,8 @@ fn process_property_notify<X: XConn, C: RuntimeConfig>(
         };
 
         if let Property::WMHints(wmhints) = wmhints {
-            if wmhints.is_set(WmHintsFlags::URGENCY_HINT) {
-                return Some(vec![ToggleUrgency(event.id)]);
-            }
+            let urgent = wmhints.is_set(WmHintsFlags::URGENCY_HINT);
+            return Some(vec![SetUrgency(event.id, urgent)]);
         }
     }
     None
@@ -235,10 +242,16 @@ fn process_client_message<X: XConn, C: RuntimeConfig>(
 ) -> Option<Vec<EventAction>> {
     use EventAction::*;
 
-    let is_fullscreen = |data: &[u32]| {
+    let has_state = |data: &[u32], state_atom: Atom| {
         data.iter()
             .flat_map(|&a| state.conn.lookup_atom(XAtom::from(a)))
-            .any(|s| s == Atom::NetWmStateFullscreen.as_ref())
+            .any(|s| s == state_atom.as_ref())
+    };
+    // 0 removes the state, 1 adds it, 2 toggles it
+    let should_set = |action: u32, is_set: bool| match action {
+        0 => false,
+        1 => true,
+        _ => !is_set,
     };
 
     let atom = match state.conn.lookup_atom(event.type_) {
@@ -248,16 +261,27 @@ fn process_client_message<X: XConn, C: RuntimeConfig>(
 
     if let ClientMessageData::U32(data) = event.data {
         match Atom::from_str(&atom) {
-            Ok(Atom::NetActiveWindow) => None, //todo
+            // source indication: 1 is an application, 2 is a pager
+            Ok(Atom::NetActiveWindow) => Some(vec![ActivateClient(event.window, data[0] == 2)]),
             Ok(Atom::NetWmDesktop) => Some(vec![ClientToWorkspace(event.window, data[0] as usize)]),
-            Ok(Atom::NetWmState) if is_fullscreen(&data[1..3]) => {
-                let should_fullscreen = [1, 2].contains(&data[0]);
+            Ok(Atom::NetCurrentDesktop) => Some(vec![GotoWorkspace(data[0] as usize)]),
+            Ok(Atom::NetWmState) if has_state(&data[1..3], Atom::NetWmStateFullscreen) => {
+                let is_fullscreen = state
+                    .lookup_client(event.window)
+                    .is_some_and(|c| c.is_fullscreen());
 
                 Some(vec![ToggleClientFullscreen(
                     event.window,
-                    should_fullscreen,
+                    should_set(data[0], is_fullscreen),
                 )])
             }
+            Ok(Atom::NetWmState) if has_state(&data[1..3], Atom::NetWmStateDemandsAttention) => {
+                let is_urgent = state
+                    .lookup_client(event.window)
+                    .is_some_and(|c| c.is_urgent());
+
+                Some(vec![SetUrgency(event.window, should_set(data[0], is_urgent))])
+            }
             _ => {
                 debug!(target: "manager::event","Got client message of type {}, data {:?}", atom, data);
                 None
@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use super::state::State;
 use super::WindowManager;
+use crate::backend::x::XWindowID;
+use crate::core::Screen;
 
 /// Arbitrary code that can be run by the window manager.
 ///
@@ -11,6 +13,20 @@ use super::WindowManager;
 /// macro's much more ergonomic interface.
 pub type Hook<X, C> = Box<dyn FnMut(&mut WindowManager<X, C>)>;
 
+/// Arbitrary code that can be run by the window manager on a given client.
+///
+/// A `ClientHook` works like a [`Hook`], but is run in response to
+/// something happening to a specific client, and receives the ID
+/// of that client.
+pub type ClientHook<X, C> = Box<dyn FnMut(&mut WindowManager<X, C>, XWindowID)>;
+
+/// Arbitrary code that can be run by the window manager when the
+/// set of connected screens changes.
+///
+/// A `ScreenHook` receives the screens from before the change,
+/// followed by the screens after it.
+pub type ScreenHook<X, C> = Box<dyn FnMut(&mut WindowManager<X, C>, &[Screen], &[Screen])>;
+
 /// Macro for creating a hook that can be run by the window manager.
 ///
 /// It accepts closure syntax, for example:
@@ -1,10 +1,12 @@
 //! The window manager itself, and associated modules.
 
 //#![allow(unused_variables, unused_imports, dead_code)]
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt;
 use std::iter::FromIterator;
 use std::process::{Command, Stdio};
+use std::time::Instant;
 
 //use std::marker::PhantomData;
 
@@ -14,13 +16,16 @@ use tracing::instrument;
 use tracing::{debug, error, info, span, warn, Level};
 
 use crate::bindings::{Keybind, Keybinds, Mousebind, Mousebinds};
-use crate::core::{Desktop, Screen, WorkspaceSpec};
+use crate::core::{Client, Desktop, Screen, WorkspaceSpec};
 use crate::layouts::{update::IntoUpdate, Layout, Layouts};
 use crate::log::DefaultErrorHandler;
-use crate::types::{Cardinal, ClientAttrs, Direction, Point, Ring, Selector};
+use crate::types::{
+    Cardinal, ClientAttrs, ClientConfig, Direction, Geometry, Point, Ring, Selector,
+};
 use crate::backend::x::{
-    event::ConfigureRequestData, input::MouseEventKind, Atom, Property, XConn, XError, XEvent,
-    XWindow, XWindowID,
+    atom::EWMH_SUPPORTED_ATOMS, core::WindowClass, event::ConfigureRequestData,
+    input::MouseEventKind, Atom, Property, Strut, WindowState, XConn, XError, XEvent, XWindow,
+    XWindowID, Xid,
 };
 use crate::{ErrorHandler, Result, ToaruError};
 
@@ -29,6 +34,8 @@ pub mod config;
 pub mod event;
 /// Macros and storage types for window manager hooks.
 pub mod hooks;
+mod restart;
+pub mod rules;
 pub mod state;
 
 #[doc(inline)]
@@ -36,9 +43,15 @@ pub use config::{Config, ToaruConfig};
 #[doc(inline)]
 pub use event::EventAction;
 #[doc(inline)]
-pub use hooks::{Hook, Hooks};
+pub use hooks::{ClientHook, Hook, Hooks, ScreenHook};
 #[doc(inline)]
-pub use state::{RuntimeConfig, WmState};
+pub use state::{ActivationPolicy, RuntimeConfig, WmState};
+
+use restart::{SavedState, SavedWorkspace, RESTART_STATE_VAR};
+use rules::{RuleAction, WindowProps};
+
+/// The name advertised on the `_NET_SUPPORTING_WM_CHECK` window.
+const WM_NAME: &str = "toaruwm";
 
 //static ERR_HANDLER: OnceLock<&dyn FnMut(ToaruError)> = OnceLock::new();
 
@@ -166,8 +179,37 @@ where
     screens: Ring<Screen>,
     /// The root window.
     root: XWindow,
+    /// The window set on `_NET_SUPPORTING_WM_CHECK`.
+    check_win: Option<XWindowID>,
     /// A main error handler function.
     ehandler: Box<dyn ErrorHandler<X, C>>,
+    /// Hooks run on each window adopted on startup.
+    adopt_hooks: Vec<ClientHook<X, C>>,
+    /// Hooks run when the set of screens changes.
+    screen_hooks: Vec<ScreenHook<X, C>>,
+    /// Hooks run on windows by window rules, by name.
+    rule_hooks: HashMap<String, ClientHook<X, C>>,
+    /// The window last set on `_NET_ACTIVE_WINDOW`.
+    active_window: Option<XWindowID>,
+    /// Windows marked as urgent, most recently marked last.
+    urgent: Vec<XWindowID>,
+    /// The space reserved by unmanaged windows such as docks.
+    struts: Vec<(XWindowID, Strut)>,
+    /// Clients asked to close, when they were last asked,
+    /// and whether they have been killed since.
+    closing: Vec<(XWindowID, Instant, bool)>,
+    /// Scratchpads that have been spawned but whose windows
+    /// have not been mapped yet.
+    pending_scratchpads: Vec<String>,
+    /// Managed windows in the order they were first managed,
+    /// as last set on `_NET_CLIENT_LIST`.
+    client_list: Vec<XWindowID>,
+    /// Managed windows from bottom to top,
+    /// as last set on `_NET_CLIENT_LIST_STACKING`.
+    stacking_list: Vec<XWindowID>,
+    /// The index of the workspace each managed window is on,
+    /// as last set on its `_NET_WM_DESKTOP`.
+    client_desktops: HashMap<XWindowID, u32>,
     /// The window currently being manipulated
     /// if `self.mousemode` is not None.
     selected: Option<XWindowID>,
@@ -206,10 +248,9 @@ where
         L: IntoIterator<Item = Box<dyn Layout>>,
     {
         let root = conn.get_root();
-        let mut screens = Ring::from_iter(
-            conn.all_outputs()
-                .unwrap_or_else(|e| fatal!("Could not get screens: {}", e)),
-        );
+        let mut screens = conn
+            .all_outputs()
+            .unwrap_or_else(|e| fatal!("Could not get screens: {}", e));
         let workspaces: Vec<WorkspaceSpec> = config.take_workspaces().into_iter().collect();
 
         let layouts = Layouts::with_layouts_validated(
@@ -231,19 +272,43 @@ where
             }
         }
 
+        distribute_workspaces(&mut screens);
         debug!("Got screens: {:?}", screens);
+        let mut screens = Ring::from_iter(screens);
         screens.set_focused(0);
 
+        // start on the workspace shown on the first screen
+        let mut desktop = Desktop::new(just_workspaces, layouts)?;
+        if let Some(idx) = screens
+            .focused()
+            .and_then(|s| s.active_workspace())
+            .and_then(|name| desktop.index_of(name))
+        {
+            desktop.set_current(idx);
+        }
+
         Ok(Self {
             conn,
             config: config.into_runtime_config(),
-            desktop: Desktop::new(just_workspaces, layouts)?,
+            desktop,
             screens,
             root,
             ehandler: Box::new(DefaultErrorHandler),
+            adopt_hooks: Vec::new(),
+            screen_hooks: Vec::new(),
+            rule_hooks: HashMap::new(),
+            urgent: Vec::new(),
+            struts: Vec::new(),
+            closing: Vec::new(),
+            pending_scratchpads: Vec::new(),
+            active_window: None,
+            client_list: Vec::new(),
+            stacking_list: Vec::new(),
+            client_desktops: HashMap::new(),
             selected: None,
             //focused: None,
             last_mouse_pos: Point { x: 0, y: 0 },
+            check_win: None,
             running: true,
             restart: false,
         })
@@ -278,17 +343,18 @@ where
         // set supported protocols
         debug!("Setting supported protocols");
         self.conn
-            .set_supported(&[
-                Atom::WmProtocols,
-                Atom::WmTakeFocus,
-                Atom::WmState,
-                Atom::WmDeleteWindow,
-            ])
+            .set_supported(EWMH_SUPPORTED_ATOMS)
             .unwrap_or_else(|e| {
                 error!("{}", e);
                 std::process::exit(1)
             });
 
+        // create the _NET_SUPPORTING_WM_CHECK window
+        debug!("Setting _NET_SUPPORTING_WM_CHECK");
+        self.set_check_window().unwrap_or_else(|e| {
+            error!("{}", e);
+        });
+
         // set _NET_NUMBER_OF_DESKTOPS
         debug!("Setting _NET_NUMBER_OF_DESKTOPS");
         self.conn
@@ -301,6 +367,20 @@ where
                 error!("{}", e);
             });
 
+        // set _NET_DESKTOP_NAMES
+        debug!("Setting _NET_DESKTOP_NAMES");
+        self.conn
+            .set_property(
+                root.id,
+                Atom::NetDesktopNames.as_ref(),
+                Property::UTF8String(
+                    self.desktop.workspaces.iter().map(|ws| ws.name.clone()).collect(),
+                ),
+            )
+            .unwrap_or_else(|e| {
+                error!("{}", e);
+            });
+
         // set _NET_CURRENT_DESKTOP
         self.conn
             .set_property(
@@ -313,6 +393,16 @@ where
                 std::process::exit(1)
             });
 
+        // clear out any client lists left behind by a previous window manager
+        debug!("Setting _NET_CLIENT_LIST and _NET_CLIENT_LIST_STACKING");
+        for atom in [Atom::NetClientList, Atom::NetClientListStacking] {
+            self.conn
+                .set_property(root.id, atom.as_ref(), Property::Window(Vec::new()))
+                .unwrap_or_else(|e| {
+                    error!("{}", e);
+                });
+        }
+
         // run hooks
         for mut hook in hooks {
             hook(self);
@@ -345,9 +435,9 @@ where
     pub fn run(&mut self, mut kb: Keybinds<X, C>, mut mb: Mousebinds<X, C>) -> Result<()> {
         // grab all existing windows
         info!(target: "", "Grabbing any existing windows");
-        for _ in self.conn.query_tree(self.root.id)? {
-            //todo
-        }
+        self.adopt_existing_windows(SavedState::take_saved())?;
+        handle_err!(self.update_client_lists(), self);
+        handle_err!(self.update_client_desktops(), self);
 
         info!(target: "", "Setup complete, beginning event loop");
         loop {
@@ -372,9 +462,13 @@ where
                 // if event handling returned an error, do not return
                 // instead, handle it internally and continue
                 handle_err!(self.handle_event(actions, &mut mb, &mut kb), self);
+                handle_err!(self.update_client_lists(), self);
+                handle_err!(self.update_client_desktops(), self);
             }
 
-            //* update window properties
+            // update window properties
+            handle_err!(self.update_active_window(), self);
+            self.escalate_closing();
 
             if !self.running {
                 break;
@@ -384,8 +478,13 @@ where
             drop(enter);
         }
 
+        // a restarted process creates its own check window
+        if let Some(check) = self.check_win.take() {
+            handle_err!(self.conn.destroy_window(check), self);
+        }
+
         if self.restart {
-            todo!("restart process")
+            return self.exec_restart();
         }
 
         Ok(())
@@ -432,12 +531,51 @@ where
         self.ehandler = Box::new(ehandler);
     }
 
+    /// Adds a hook to be run on each existing window that is
+    /// adopted when the event loop starts.
+    ///
+    /// The hook receives the ID of the adopted window.
+    pub fn add_adopt_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&mut WindowManager<X, C>, XWindowID) + 'static,
+    {
+        self.adopt_hooks.push(Box::new(hook));
+    }
+
+    /// Adds a hook that window rules can run on the windows they
+    /// match, using [`RuleAction::Hook`] with the same name.
+    ///
+    /// The hook receives the ID of the window being mapped.
+    pub fn add_rule_hook<S, F>(&mut self, name: S, hook: F)
+    where
+        S: Into<String>,
+        F: FnMut(&mut WindowManager<X, C>, XWindowID) + 'static,
+    {
+        self.rule_hooks.insert(name.into(), Box::new(hook));
+    }
+
+    /// Adds a hook to be run whenever screens are added, removed
+    /// or reconfigured.
+    ///
+    /// The hook receives the screens from before and after the change.
+    pub fn add_screen_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&mut WindowManager<X, C>, &[Screen], &[Screen]) + 'static,
+    {
+        self.screen_hooks.push(Box::new(hook));
+    }
+
     /// Quits the event loop.
     pub fn quit(&mut self) {
         self.running = false;
     }
 
     /// Restarts the window manager in-place.
+    ///
+    /// Once the event loop exits, the state of the desktop is saved,
+    /// and the running binary is re-executed with the same arguments.
+    /// The new process then restores the saved state when its own
+    /// event loop starts, so no windows are lost.
     pub fn restart(&mut self) {
         self.running = false;
         self.restart = true;
@@ -458,34 +596,45 @@ where
     C: RuntimeConfig,
 {
     /// Goes to the specified workspace.
+    ///
+    /// If the workspace is shown on, or belongs to, another screen,
+    /// that screen is focused and the pointer is warped to it.
+    /// Otherwise, the workspace takes the place of the one currently
+    /// shown on the focused screen.
     #[instrument(level = "debug", skip(self))]
     pub fn goto_workspace(&mut self, name: &str) {
-        handle_err!(
-            self.desktop.go_to(
-                name,
-                &self.conn,
-                self.screens.focused().unwrap(),
-                &self.config
-            ),
-            self
-        );
+        handle_err!(self.show_workspace(name), self);
     }
 
-    /// Cycles the focused workspace.
+    /// Cycles through the workspaces of the focused screen.
     pub fn cycle_workspace(&mut self, direction: Direction) {
-        handle_err!(
-            self.desktop.cycle_to(
-                &self.conn,
-                self.screens.focused().unwrap(),
-                &self.config,
-                direction
-            ),
-            self
-        );
+        let Some(scr) = self.screens.focused() else {
+            return
+        };
+        if scr.wix.is_empty() {
+            return
+        }
+
+        let len = scr.wix.len();
+        let pos = scr
+            .wix
+            .iter()
+            .position(|ws| scr.active.as_ref() == Some(ws))
+            .unwrap_or(0);
+        let next = match direction {
+            Direction::Forward => (pos + 1) % len,
+            Direction::Backward => (pos + len - 1) % len,
+        };
+
+        let name = scr.wix[next].clone();
+        self.goto_workspace(&name);
     }
 
     /// Sends the focused window to the specified workspace.
     pub fn send_focused_to(&mut self, name: &str) {
+        let Some(id) = self.focused_client_id() else {
+            return
+        };
         handle_err!(
             self.desktop.send_focused_to(
                 name,
@@ -495,28 +644,142 @@ where
             ),
             self
         );
+        self.display_sent_window(id, name);
     }
 
     /// Sends the focused window to the specified workspace and then switches to it.
     pub fn send_window_and_switch(&mut self, name: &str) {
+        self.send_focused_to(name);
+        self.goto_workspace(name);
+    }
+
+    /// Toggles the named scratchpad.
+    ///
+    /// If the scratchpad has no window yet, its command is run, and the
+    /// window is shown once it is mapped. If it is shown on the current
+    /// workspace, it is hidden, and otherwise it is brought to the
+    /// current workspace, floating and centred on the focused screen.
+    pub fn toggle_scratchpad(&mut self, name: &str) {
+        let Some(spec) = self.config.scratchpads().iter().find(|s| s.name() == name) else {
+            warn!("No scratchpad named {}", name);
+            return
+        };
+        let Some(id) = self.desktop.scratchpad(name) else {
+            let (cmd, args) = (spec.command().to_string(), spec.args().to_vec());
+            if !self.pending_scratchpads.iter().any(|p| p == name) {
+                self.pending_scratchpads.push(name.into());
+            }
+            self.run_external(cmd, &args);
+            return
+        };
+        let scr = self.screens.focused().unwrap();
+        if self.desktop.current().contains_window(id) {
+            handle_err!(self.desktop.dismiss_scratchpad(name, &self.conn, scr, &self.config), self);
+        } else {
+            handle_err!(self.desktop.summon_scratchpad(name, &self.conn, scr, &self.config), self);
+        }
+    }
+
+    /// Focuses the screen at index `idx`.
+    ///
+    /// The pointer is warped to the centre of the screen, and the
+    /// workspace shown on it becomes the current workspace.
+    pub fn focus_screen(&mut self, idx: usize) {
+        let Some(scr) = self.screens.get(idx) else {
+            warn!("No screen with index {}", idx);
+            return
+        };
+        let geom = scr.true_geom();
+
+        self.set_screen_focus(idx);
         handle_err!(
-            self.desktop.send_focused_to(
-                name,
-                &self.conn,
-                self.screens.focused().unwrap(),
-                &self.config
-            ),
-            self
-        );
-        handle_err!(
-            self.desktop.go_to(
-                name,
-                &self.conn,
-                self.screens.focused().unwrap(),
-                &self.config
+            self.conn.warp_pointer(
+                self.root.id,
+                geom.x + geom.width / 2,
+                geom.y + geom.height / 2
             ),
             self
         );
+        if let Some(id) = self.focused_client_id() {
+            self.desktop
+                .current_mut()
+                .focus_window(id, &self.conn, &self.config);
+        }
+    }
+
+    /// Goes to the window most recently marked as urgent, if any,
+    /// switching to its workspace and focusing it.
+    pub fn focus_urgent(&mut self) {
+        // windows lose their urgency when focused, so prune those first
+        let desktop = &self.desktop;
+        self.urgent.retain(|id| {
+            desktop
+                .workspaces
+                .iter()
+                .any(|ws| ws.windows.lookup(*id).is_some_and(|c| c.is_urgent()))
+        });
+
+        let Some(&id) = self.urgent.last() else {
+            return
+        };
+        let Some(name) = self
+            .desktop
+            .workspaces
+            .iter()
+            .find(|ws| ws.contains_window(id))
+            .map(|ws| ws.name.clone())
+        else {
+            return
+        };
+        handle_err!(self.show_workspace(&name), self);
+        handle_err!(self.update_focus(id), self);
+    }
+
+    /// Cycles the focused screen in the given direction.
+    pub fn cycle_screen(&mut self, direction: Direction) {
+        self.screens.cycle_focus(direction);
+        if let Some(idx) = self.screens.focused_idx() {
+            self.focus_screen(idx);
+        }
+    }
+
+    /// Moves the workspace `name` to the screen at index `idx`,
+    /// shows it there and focuses that screen.
+    ///
+    /// If the screen it was moved from was showing it, that screen
+    /// switches to another one of its workspaces, if it has any.
+    pub fn move_workspace_to_screen(&mut self, name: &str, idx: usize) {
+        if self.desktop.index_of(name).is_none() {
+            handle_err!(Err::<(), _>(ToaruError::UnknownWorkspace(name.into())), self);
+            return
+        }
+        if idx >= self.screens.len() {
+            warn!("No screen with index {}", idx);
+            return
+        }
+
+        let owner = self.screen_owning(name);
+        if owner != Some(idx) {
+            if let Some(owner) = owner {
+                self.screens[owner].wix.retain(|ws| ws != name);
+            }
+            self.screens[idx].wix.push(name.into());
+        }
+
+        if let Some(from) = self.screen_showing(name).filter(|&from| from != idx) {
+            self.screens[from].active = None;
+            let replacement = self.screens[from]
+                .wix
+                .iter()
+                .find(|ws| self.screen_showing(ws).is_none())
+                .cloned();
+            if let Some(ws) = replacement {
+                self.show_on_screen(&ws, from);
+            }
+        }
+
+        self.show_on_screen(name, idx);
+        self.focus_screen(idx);
     }
 }
 
@@ -619,7 +882,13 @@ where
 
             let current = self.desktop.current_mut();
             if let Some(win) = current.windows.lookup_mut(win) {
+                let (height, width) = (win.height(), win.width());
                 win.do_resize(&self.conn, dx, dy);
+                // size hints may not let the whole change through, so only
+                // count what was applied, letting the rest build up
+                self.last_mouse_pos.x += win.width() - width;
+                self.last_mouse_pos.y += win.height() - height;
+                return
             } else {
                 error!("Tried to move untracked window {}", win)
             }
@@ -665,11 +934,31 @@ where
     }
 
     /// Closes the focused window.
+    ///
+    /// Clients that support WM_DELETE_WINDOW are asked to close,
+    /// and are killed if they do not within the configured
+    /// `close_timeout`. Other clients are killed straight away.
     pub fn close_focused_window(&mut self) {
-        if let Some(window) = self.desktop.current_mut().windows.focused() {
-            handle_err!(self.conn.destroy_window(window.id()), self);
-        } else {
+        let Some(client) = self.desktop.current_client() else {
             warn!("Could not find focused window to destroy");
+            return
+        };
+        let id = client.id();
+        if client.request_close(&self.conn) {
+            self.closing.retain(|(w, ..)| *w != id);
+            self.closing.push((id, Instant::now(), false));
+        } else {
+            handle_err!(self.conn.kill_client(id), self);
+        }
+    }
+
+    /// Forcibly kills the client of the focused window,
+    /// without asking it to close first.
+    pub fn kill_focused_window(&mut self) {
+        if let Some(id) = self.focused_client_id() {
+            handle_err!(self.conn.kill_client(id), self);
+        } else {
+            warn!("Could not find focused window to kill");
         }
     }
 }
@@ -684,7 +973,18 @@ where
     /// Receive the next event from the connection and process it
     /// into a actions to be taken by the window manager.
     fn process_next_event(&mut self) -> Result<Option<Vec<EventAction>>> {
-        let Some(event) = self.conn.poll_next_event()? else {return Ok(None)};
+        // wake up in time to deal with clients that have not closed
+        let timeout = self
+            .closing
+            .iter()
+            .map(|(_, since, _)| *since + self.config.close_timeout())
+            .min()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        let event = match timeout {
+            Some(timeout) => self.conn.poll_next_event_timeout(timeout)?,
+            None => self.conn.poll_next_event()?,
+        };
+        let Some(event) = event else {return Ok(None)};
         Ok(EventAction::from_xevent(event, self.state()))
     }
 
@@ -706,16 +1006,20 @@ where
                 ClientNameChange(id) => self.client_name_change(id)?,
                 ScreenReconfigure => self.screen_reconfigure()?,
                 SetFocusedScreen(pt) => self.set_focused_screen(pt)?,
-                DestroyClient(_) => {}
+                ClientStrutChange(id) => self.update_strut(id),
+                ClientSizeHintsChange(id) => self.update_size_hints(id)?,
+                DestroyClient(id) => self.destroy_client(id)?,
                 MapTrackedClient(id) => self.map_tracked_client(id)?,
                 MapUntrackedClient(id) => self.map_untracked_client(id)?,
                 UnmapClient(id) => self.unmap_client(id)?,
                 ConfigureClient(data) => self.configure_client(data)?,
                 ClientToWorkspace(id, idx) => self.client_to_workspace(id, idx)?,
+                GotoWorkspace(idx) => self.goto_workspace_idx(idx)?,
                 RunKeybind(kb, id) => self.run_keybind(kb, keybinds, id),
                 RunMousebind(mb, id, pt) => self.run_mousebind(mb, mousebinds, id, pt)?,
+                ActivateClient(id, from_user) => self.activate_client(id, from_user)?,
                 ToggleClientFullscreen(id, should_fs) => self.set_fullscreen(id, should_fs)?,
-                ToggleUrgency(id) => self.toggle_urgency(id)?,
+                SetUrgency(id, urgent) => self.set_urgency(id, urgent)?,
                 HandleError(err, evt) => self.handle_error(err, evt),
             }
         }
@@ -732,7 +1036,18 @@ where
         // set unfocused border colour
         // update focus internally
         // if client not found, set focus to root window
-        let target = if self.desktop.is_managing(id) {
+        // focus moving to a window on another screen takes the screen focus with it
+        let shown_on = self
+            .desktop
+            .workspaces
+            .iter()
+            .find(|ws| ws.contains_window(id))
+            .and_then(|ws| self.screen_showing(&ws.name));
+        if let Some(idx) = shown_on.filter(|&idx| Some(idx) != self.screens.focused_idx()) {
+            self.set_screen_focus(idx);
+        }
+
+        let target = if self.desktop.current().contains_window(id) {
             id
         } else {
             match self.focused_client_id() {
@@ -747,9 +1062,333 @@ where
         self.desktop
             .current_mut()
             .focus_window(target, &self.conn, &self.config);
+        self.update_active_window()
+    }
+
+    /// Creates the window advertised on `_NET_SUPPORTING_WM_CHECK`,
+    /// which tells clients that an EWMH-compliant window manager
+    /// is running.
+    fn set_check_window(&mut self) -> Result<()> {
+        let geom = Geometry::new(-1, -1, 1, 1);
+        let check = self.conn.create_window(WindowClass::CheckWin, geom, false)?;
+        self.check_win = Some(check);
+
+        for id in [self.root.id, check] {
+            self.conn.set_property(
+                id,
+                Atom::NetSupportingWmCheck.as_ref(),
+                Property::Window(vec![check]),
+            )?;
+        }
+        self.conn.set_property(
+            check,
+            Atom::NetWmName.as_ref(),
+            Property::UTF8String(vec![WM_NAME.into()]),
+        )?;
+        Ok(())
+    }
+
+    /// Sets `_NET_ACTIVE_WINDOW` to the focused client, if it has changed.
+    fn update_active_window(&mut self) -> Result<()> {
+        let active = self.focused_client_id();
+        if active == self.active_window {
+            return Ok(())
+        }
+        self.conn.set_property(
+            self.root.id,
+            Atom::NetActiveWindow.as_ref(),
+            Property::Window(vec![active.unwrap_or(Xid(0))]),
+        )?;
+        self.active_window = active;
         Ok(())
     }
 
+    /// Handles a request to activate a client.
+    ///
+    /// Requests from the user are always honoured, otherwise
+    /// the configured [`ActivationPolicy`] decides whether to
+    /// steal the focus or mark the client as urgent.
+    #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
+    fn activate_client(&mut self, id: XWindowID, from_user: bool) -> Result<()> {
+        let Some((ws, _)) = self.desktop.retrieve_mut(id) else {
+            return Err(ToaruError::UnknownClient(id))
+        };
+
+        if from_user || self.config.activation_policy() == ActivationPolicy::Focus {
+            let name = ws.name.clone();
+            self.show_workspace(&name)?;
+            return self.update_focus(id);
+        }
+
+        self.set_urgency(id, true)
+    }
+
+    /// Adopts all top-level windows that already exist on the server.
+    ///
+    /// If we were restarted in-place, windows tracked by the previous
+    /// process are restored using its saved state.
+    fn adopt_existing_windows(&mut self, saved: Option<SavedState>) -> Result<()> {
+        let existing = self.conn.query_tree(self.root.id)?;
+
+        let restored = if let Some(saved) = saved {
+            info!(target: "", "Restoring state from previous process");
+            self.restore_state(&saved, &existing)
+        } else {
+            Vec::new()
+        };
+
+        for id in existing {
+            if restored.contains(&id) {
+                self.run_adopt_hooks(id);
+                continue;
+            }
+            if let Some(strut) = self.existing_strut(id) {
+                self.struts.push((id, strut));
+                continue;
+            }
+            if !self.should_adopt(id) {
+                continue;
+            }
+            debug!("Adopting existing window {}", id);
+
+            // restore the window to its previous workspace if it had one
+            let idx = match self.conn.get_wm_desktop(id) {
+                Some(idx) if (idx as usize) < self.desktop.workspaces.len() => idx as usize,
+                _ => self.desktop.current_idx(),
+            };
+            self.adopt_window(id, idx);
+            self.run_adopt_hooks(id);
+        }
+        self.apply_struts();
+
+        Ok(())
+    }
+
+    /// Returns the strut of an existing unmanaged window that is mapped.
+    fn existing_strut(&self, id: XWindowID) -> Option<Strut> {
+        let attrs = self.conn.get_window_attributes(id).ok()?;
+        if attrs.override_redirect || !attrs.map_state.is_viewable() || self.conn.should_manage(id) {
+            return None
+        }
+        self.conn.get_strut(id)
+    }
+
+    fn run_adopt_hooks(&mut self, id: XWindowID) {
+        let mut hooks = std::mem::take(&mut self.adopt_hooks);
+        for hook in hooks.iter_mut() {
+            hook(self, id);
+        }
+        self.adopt_hooks = hooks;
+    }
+
+    /// Restores the desktop to a state saved before an in-place restart.
+    ///
+    /// Returns the IDs of all windows that were restored.
+    fn restore_state(&mut self, saved: &SavedState, existing: &[XWindowID]) -> Vec<XWindowID> {
+        // show the workspaces each screen was showing before
+        for (idx, name) in &saved.visible {
+            if self.desktop.index_of(name).is_none() {
+                continue
+            }
+            for scr in self.screens.iter_mut() {
+                if scr.idx == *idx && scr.wix.contains(name) {
+                    scr.active = Some(name.clone());
+                } else if scr.active.as_ref() == Some(name) {
+                    scr.active = None;
+                }
+            }
+        }
+        let owner = self.screen_owning(&saved.current);
+        if let Some(idx) = owner.filter(|_| self.screen_showing(&saved.current).is_none()) {
+            self.screens[idx].active = Some(saved.current.clone());
+        }
+        distribute_workspaces(self.screens.items.make_contiguous());
+
+        // go to the previously focused workspace
+        if let Some(idx) = self.screen_showing(&saved.current) {
+            self.set_screen_focus(idx);
+        }
+
+        let mut restored = Vec::new();
+        for saved_ws in &saved.workspaces {
+            let Some(idx) = self.desktop.index_of(&saved_ws.name) else {
+                warn!("workspace {} no longer exists", saved_ws.name);
+                continue
+            };
+            self.restore_layouts(idx, saved_ws);
+            let shown = self.screen_showing(&saved_ws.name).is_some();
+
+            // put_window pushes to the front, so go in reverse to keep the order
+            for saved_client in saved_ws.clients.iter().rev() {
+                let id = saved_client.id;
+                let Ok(attrs) = self.conn.get_window_attributes(id) else {
+                    continue
+                };
+                if !existing.contains(&id) || attrs.override_redirect {
+                    continue;
+                }
+
+                let mut client = if saved_client.floating {
+                    let mut client = Client::outside_layout(id, &self.conn);
+                    client.set_geometry(saved_client.geom);
+                    client
+                } else {
+                    Client::new(id, &self.conn)
+                };
+                // placement comes from the saved state, but the rest of the rules still hold
+                let props = WindowProps::query(&self.conn, id);
+                let actions = rules::matching_actions(self.config.rules(), &props);
+                rules::apply_to_client(&mut client, &actions);
+                client.set_supported(&self.conn);
+                let border = client.border_px().unwrap_or(self.config.border_px());
+                client.set_border_width(&self.conn, border);
+                if !shown {
+                    client.unmap(&self.conn);
+                }
+                self.desktop.workspaces[idx].put_window(client);
+                restored.push(id);
+            }
+
+            let ws = &mut self.desktop.workspaces[idx];
+            if let Some(focused) = saved_ws.focused.filter(|id| ws.contains_window(*id)) {
+                ws.windows.set_focused_by_winid(focused);
+                ws.focuses.set_focused_by_winid(focused);
+            }
+        }
+
+        // shown scratchpads were restored with their workspace,
+        // and hidden ones are kept unmapped
+        for (name, id) in &saved.scratchpads {
+            if self.desktop.is_managing(*id) {
+                self.desktop.set_scratchpad(name, *id);
+            } else if existing.contains(id) {
+                let mut client = Client::outside_layout(*id, &self.conn);
+                client.set_supported(&self.conn);
+                client.set_border_width(&self.conn, self.config.border_px());
+                self.desktop.add_scratchpad(name, client);
+                restored.push(*id);
+            }
+        }
+
+        self.activate_visible();
+
+        restored
+    }
+
+    /// Restores the active layout and layout parameters of a workspace.
+    fn restore_layouts(&mut self, idx: usize, saved: &SavedWorkspace) {
+        let layouts = &mut self.desktop.workspaces[idx].layouts;
+
+        for (name, params) in &saved.params {
+            if let Some((_, layout)) = layouts.element_by(|l| l.name() == name) {
+                layout.restore_params(params);
+            }
+        }
+        if let Some((i, _)) = layouts.element_by(|l| l.name() == saved.layout) {
+            layouts.set_focused(i);
+        }
+    }
+
+    /// Saves the state of the desktop and re-executes the running binary.
+    ///
+    /// This only returns if the restart failed.
+    fn exec_restart(&mut self) -> Result<()> {
+        use std::os::unix::process::CommandExt;
+
+        info!(target: "", "Restarting in-place");
+        let state = SavedState::from_desktop(&self.desktop, &self.screens);
+
+        // give up substructure redirection so the new process can take over
+        self.conn
+            .change_window_attributes(self.root.id, &[ClientAttrs::DisableClientEvents])?;
+        // and make sure the server knows before we go
+        self.conn.flush()?;
+
+        let exe = std::env::current_exe()
+            .map_err(|e| ToaruError::Restart(format!("could not find executable: {}", e)))?;
+        let err = Command::new(exe)
+            .args(std::env::args_os().skip(1))
+            .env(RESTART_STATE_VAR, state.to_string())
+            .exec();
+
+        Err(ToaruError::Restart(err.to_string()))
+    }
+
+    /// Checks whether an existing window should be adopted.
+    fn should_adopt(&self, id: XWindowID) -> bool {
+        if self.desktop.is_managing(id) {
+            return false
+        }
+        let Ok(attrs) = self.conn.get_window_attributes(id) else {
+            return false
+        };
+
+        if attrs.override_redirect || !self.conn.should_manage(id) {
+            return false;
+        }
+
+        match self.conn.get_wm_state(id) {
+            Some(WindowState::Withdrawn) | Some(WindowState::Iconic) => false,
+            // windows on unfocused workspaces are unmapped but still Normal
+            Some(WindowState::Normal) => true,
+            None => attrs.map_state.is_viewable(),
+        }
+    }
+
+    /// Places an existing window on the workspace at `idx`.
+    fn adopt_window(&mut self, id: XWindowID, idx: usize) {
+        let client = self.new_client(id, idx, false);
+        self.place_client(client, idx);
+    }
+
+    /// Creates a client for a window about to be placed on the
+    /// workspace at `idx`.
+    ///
+    /// The client is kept off the layout if `float` is set, if it
+    /// should float, or if the workspace is floating.
+    fn new_client(&self, id: XWindowID, idx: usize, float: bool) -> Client {
+        if float
+            || self.conn.should_float(id, self.config.float_classes())
+            || self.desktop.workspaces[idx].is_floating()
+        {
+            Client::outside_layout(id, &self.conn)
+        } else {
+            Client::new(id, &self.conn)
+        }
+    }
+
+    /// Places a client on the workspace at `idx`, mapping it only
+    /// if the workspace is shown on a screen.
+    ///
+    /// Only clients placed on the current workspace are focused.
+    fn place_client(&mut self, client: Client, idx: usize) {
+        let id = client.id();
+        let shown_on = self.screen_showing(&self.desktop.workspaces[idx].name);
+
+        if idx == self.desktop.current_idx() {
+            let scr = match shown_on {
+                Some(s) => &self.screens[s],
+                None => self.screens.focused().unwrap(),
+            };
+            let current = self.desktop.current_mut();
+            current.add_client(client, &self.conn, scr, &self.config);
+            return;
+        }
+
+        let ws = &mut self.desktop.workspaces[idx];
+        ws.stash_window(client, &self.conn, &self.config);
+        if let Some(s) = shown_on {
+            // the workspace is shown on another screen
+            ws.relayout(&self.conn, &self.screens[s], &self.config);
+            if let Some(client) = ws.windows.lookup_mut(id) {
+                client.map(&self.conn);
+            }
+        } else if let Some(client) = ws.windows.lookup_mut(id) {
+            // the window gets mapped again when its workspace is activated
+            client.unmap(&self.conn);
+        }
+    }
+
     fn focused_client_id(&self) -> Option<XWindowID> {
         self.desktop.current_client().map(|c| c.id())
     }
@@ -767,15 +1406,16 @@ where
 
         // get the screen to focus to
         let to_focus = self.screens.index(Selector::Condition(&|s| {
-            s.effective_geom().contains_point(ptr)
+            s.true_geom().contains_point(ptr)
         }));
 
         if let Some(idx) = to_focus {
-            self.focus_screen(idx);
+            if Some(idx) != self.screens.focused_idx() {
+                self.set_screen_focus(idx);
+            }
         } else {
             return Err(ToaruError::InvalidPoint(ptr.x, ptr.y));
         }
-        //todo: if per-screen workspaces, need to focus workspace also
         Ok(())
     }
 
@@ -790,32 +1430,78 @@ where
     }
 
     #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
+    /// Maps a new window and places it according to the window
+    /// rules that match it.
+    ///
+    /// Windows go on the current workspace unless a rule says
+    /// otherwise, except for dialogs, which float over their
+    /// parent on the parent's workspace.
     fn map_tracked_client(&mut self, id: XWindowID) -> Result<()> {
-        let current = self.desktop.current_mut();
-        if self.conn.should_float(id, self.config.float_classes()) || current.is_floating() {
-            current.add_window_off_layout(
-                id,
-                &self.conn,
-                self.screens.focused().unwrap(),
-                &self.config,
-            )
-        } else {
-            current.add_window_on_layout(
-                id,
-                &self.conn,
-                self.screens.focused().unwrap(),
-                &self.config,
-            )
+        let props = WindowProps::query(&self.conn, id);
+        let scratchpad = self.pending_scratchpads.iter().position(|name| {
+            self.config.scratchpads().iter().any(|s| s.name() == name && s.matches(&props))
+        });
+        if let Some(idx) = scratchpad {
+            let name = self.pending_scratchpads.remove(idx);
+            let client = Client::outside_layout(id, &self.conn);
+            self.desktop.add_scratchpad(&name, client);
+            let scr = self.screens.focused().unwrap();
+            return self.desktop.summon_scratchpad(&name, &self.conn, scr, &self.config)
+        }
+        let actions = rules::matching_actions(self.config.rules(), &props);
+
+        let parent = self
+            .conn
+            .get_wm_transient_for(id)
+            .and_then(|p| self.desktop.workspaces.iter().position(|ws| ws.contains_window(p)));
+        let target = actions.iter().rev().find_map(|a| match a {
+            RuleAction::Workspace(name) => self.desktop.index_of(name),
+            _ => None,
+        });
+        let idx = target.or(parent).unwrap_or(self.desktop.current_idx());
+
+        let float = parent.is_some()
+            || actions
+                .iter()
+                .any(|a| matches!(a, RuleAction::Float(_) | RuleAction::SkipLayout));
+        let mut client = self.new_client(id, idx, float);
+        for action in actions.iter() {
+            if let RuleAction::Float(geom) = action {
+                client.set_and_update_geometry(&self.conn, *geom);
+            }
+        }
+        rules::apply_to_client(&mut client, &actions);
+        self.place_client(client, idx);
+
+        for action in actions {
+            match action {
+                RuleAction::Fullscreen => self.set_fullscreen(id, true)?,
+                RuleAction::Hook(name) => self.run_rule_hook(&name, id),
+                _ => {}
+            }
         }
         Ok(())
     }
 
-    fn map_untracked_client(&self, id: XWindowID) -> Result<()> {
-        Ok(self.conn.map_window(id)?)
+    fn run_rule_hook(&mut self, name: &str, id: XWindowID) {
+        let Some(mut hook) = self.rule_hooks.remove(name) else {
+            warn!("No rule hook named {}", name);
+            return
+        };
+        hook(self, id);
+        self.rule_hooks.insert(name.into(), hook);
+    }
+
+    fn map_untracked_client(&mut self, id: XWindowID) -> Result<()> {
+        self.conn.map_window(id)?;
+        // docks reserve space at the edges of the screen
+        self.update_strut(id);
+        Ok(())
     }
 
     #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
     fn unmap_client(&mut self, id: XWindowID) -> Result<()> {
+        self.release_strut(id);
         // the client itself handles the unmapping, so we just handle internal state
         self.desktop.current_mut().del_window(
             id,
@@ -827,25 +1513,248 @@ where
     }
 
     #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
-    fn configure_client(&mut self, data: ConfigureRequestData) -> Result<()> {
-        //todo
+    fn destroy_client(&mut self, id: XWindowID) -> Result<()> {
+        self.release_strut(id);
+        self.desktop.forget_scratchpad(id);
+        let Some((ws, _)) = self.desktop.retrieve_mut(id) else {
+            return Ok(())
+        };
+        // only relayout the workspace if it is being shown
+        match self.screens.iter().find(|s| s.active.as_ref() == Some(&ws.name)) {
+            Some(scr) => {
+                ws.del_window(id, &self.conn, scr, &self.config)?;
+            }
+            None => {
+                ws.forget_window(id);
+            }
+        }
         Ok(())
     }
 
-    #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
-    fn client_to_workspace(&mut self, id: XWindowID, idx: usize) -> Result<()> {
-        let name = match self.desktop.get(idx) {
-            Some(ws) => ws.name.to_string(),
-            None => return Ok(()),
-        };
+    /// Reads the strut set by an unmanaged window and reserves
+    /// space for it, or releases its space if it no longer has one.
+    fn update_strut(&mut self, id: XWindowID) {
+        let strut = self.conn.get_strut(id);
+        let existing = self.struts.iter().position(|(w, _)| *w == id);
+
+        match (strut, existing) {
+            (Some(strut), Some(idx)) => self.struts[idx].1 = strut,
+            (Some(strut), None) => self.struts.push((id, strut)),
+            (None, Some(idx)) => {
+                self.struts.remove(idx);
+            }
+            (None, None) => return,
+        }
+        debug!("Updating struts: {:?}", self.struts);
+        self.apply_struts();
+    }
 
-        self.desktop.send_window_to(
-            id,
-            &name,
-            &self.conn,
-            self.screens.focused().unwrap(),
+    /// Releases the space reserved by an unmanaged window, if any.
+    fn release_strut(&mut self, id: XWindowID) {
+        let len = self.struts.len();
+        self.struts.retain(|(w, _)| *w != id);
+        if self.struts.len() != len {
+            debug!("Released strut of window {}", id);
+            self.apply_struts();
+        }
+    }
+
+    /// Recalculates the effective area of each screen from the struts
+    /// currently reserved, updates `_NET_WORKAREA` and relays out
+    /// the workspaces being shown.
+    fn apply_struts(&mut self) {
+        let root = self.conn.get_geometry(self.root.id).unwrap_or(self.root.geom);
+        for scr in self.screens.iter_mut() {
+            scr.reset_effective();
+            // docks on the same edge overlap, so only the largest counts
+            let mut reserved: Vec<(Cardinal, i32)> = Vec::new();
+            for (dir, trim) in self
+                .struts
+                .iter()
+                .flat_map(|(_, strut)| strut.reserved(scr.true_geom(), root))
+            {
+                match reserved.iter_mut().find(|(d, _)| *d == dir) {
+                    Some((_, max)) => *max = (*max).max(trim),
+                    None => reserved.push((dir, trim)),
+                }
+            }
+            for (dir, trim) in reserved {
+                scr.update_effective(dir, trim);
+            }
+        }
+
+        // each workspace gets the work area of the screen it belongs to
+        let workarea = self
+            .desktop
+            .workspaces
+            .iter()
+            .flat_map(|ws| {
+                let scr = self
+                    .screen_owning(&ws.name)
+                    .and_then(|idx| self.screens.get(idx))
+                    .or_else(|| self.screens.focused())
+                    .unwrap();
+                let Geometry { x, y, height, width } = scr.effective_geom();
+                [x as u32, y as u32, width as u32, height as u32]
+            })
+            .collect();
+        handle_err!(
+            self.conn.set_property(
+                self.root.id,
+                Atom::NetWorkarea.as_ref(),
+                Property::U32List("CARDINAL".into(), workarea),
+            ),
+            self
+        );
+
+        for scr in self.screens.iter() {
+            let Some(ws) = scr.active.as_ref().and_then(|name| self.desktop.find_mut(name)) else {
+                continue
+            };
+            ws.relayout(&self.conn, scr, &self.config);
+        }
+    }
+
+    /// Updates `_NET_CLIENT_LIST` and `_NET_CLIENT_LIST_STACKING`
+    /// on the root window if the set of managed windows or their
+    /// stacking order has changed.
+    fn update_client_lists(&mut self) -> Result<()> {
+        let desktop = &self.desktop;
+
+        let mut clients = self.client_list.clone();
+        clients.retain(|id| desktop.is_managing(*id));
+        for ws in desktop.workspaces.iter() {
+            for id in ws.clients().map(|c| c.id()) {
+                if !clients.contains(&id) {
+                    clients.push(id);
+                }
+            }
+        }
+
+        // query_tree returns the root's children from bottom to top
+        let stacking: Vec<XWindowID> = self
+            .conn
+            .query_tree(self.root.id)?
+            .into_iter()
+            .filter(|id| clients.contains(id))
+            .collect();
+
+        if clients != self.client_list {
+            self.conn.set_property(
+                self.root.id,
+                Atom::NetClientList.as_ref(),
+                Property::Window(clients.clone()),
+            )?;
+            self.client_list = clients;
+        }
+        if stacking != self.stacking_list {
+            self.conn.set_property(
+                self.root.id,
+                Atom::NetClientListStacking.as_ref(),
+                Property::Window(stacking.clone()),
+            )?;
+            self.stacking_list = stacking;
+        }
+        Ok(())
+    }
+
+    /// Sets `_NET_WM_DESKTOP` on each managed window whose
+    /// workspace has changed since it was last set.
+    fn update_client_desktops(&mut self) -> Result<()> {
+        self.client_desktops.retain(|id, _| self.desktop.is_managing(*id));
+
+        for (idx, ws) in self.desktop.workspaces.iter().enumerate() {
+            for id in ws.clients().map(|c| c.id()) {
+                if self.client_desktops.get(&id) == Some(&(idx as u32)) {
+                    continue
+                }
+                self.conn.set_property(
+                    id,
+                    Atom::NetWmDesktop.as_ref(),
+                    Property::Cardinal(idx as u32),
+                )?;
+                self.client_desktops.insert(id, idx as u32);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
+    fn configure_client(&mut self, data: ConfigureRequestData) -> Result<()> {
+        if data.is_root {
+            return Ok(())
+        }
+        let hints = self.conn.get_wm_size_hints(data.id);
+        let constrain = |geom: Geometry| {
+            let Some(hints) = hints else { return geom };
+            let (height, width) = hints.constrain(geom.height, geom.width);
+            Geometry { height, width, ..geom }
+        };
+
+        // we don't manage this window, so honour its request as-is
+        let Some((ws, _)) = self.desktop.retrieve_mut(data.id) else {
+            let mut changes = Vec::new();
+            if data.changes_geometry() {
+                let geom = constrain(data.apply_to(self.conn.get_geometry(data.id)?));
+                changes.push(ClientConfig::Position(geom));
+            }
+            if let Some(mode) = data.stack_mode {
+                changes.push(ClientConfig::StackingMode(mode));
+            }
+            return Ok(self.conn.configure_window(data.id, &changes)?);
+        };
+
+        let floating = ws.is_floating();
+        let client = ws.windows.lookup_mut(data.id).unwrap();
+
+        if client.is_fullscreen() {
+            // fullscreen windows stay where they are
+            self.conn.send_configure_notify(data.id, client.geometry(), 0)?;
+        } else if client.is_off_layout() || floating {
+            // floating windows can go wherever they want
+            if data.changes_geometry() {
+                let geom = constrain(data.apply_to(client.geometry()));
+                client.set_and_update_geometry(&self.conn, geom);
+            }
+        } else {
+            // the layout decides where tiled windows go, so tell
+            // the client where it actually is
+            self.conn.send_configure_notify(data.id, client.geometry(), client.border_width())?;
+        }
+
+        if let Some(mode) = data.stack_mode {
+            ws.restack_window(&self.conn, data.id, mode);
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
+    fn client_to_workspace(&mut self, id: XWindowID, idx: usize) -> Result<()> {
+        let name = match self.desktop.get(idx) {
+            Some(ws) => ws.name.to_string(),
+            None => return Ok(()),
+        };
+
+        self.desktop.send_window_to(
+            id,
+            &name,
+            &self.conn,
+            self.screens.focused().unwrap(),
             &self.config,
-        )
+        )?;
+        self.display_sent_window(id, &name);
+        Ok(())
+    }
+
+    #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
+    fn goto_workspace_idx(&mut self, idx: usize) -> Result<()> {
+        let name = match self.desktop.get(idx) {
+            Some(ws) => ws.name.to_string(),
+            None => return Ok(()),
+        };
+
+        self.show_workspace(&name)
     }
 
     /// Runs the keybind.
@@ -890,21 +1799,286 @@ where
         Ok(())
     }
 
-    fn set_fullscreen(&mut self, _id: XWindowID, _should_fullscreen: bool) -> Result<()> {
-        todo!()
+    #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
+    fn set_fullscreen(&mut self, id: XWindowID, should_fullscreen: bool) -> Result<()> {
+        let Some((ws, _)) = self.desktop.retrieve_mut(id) else {
+            return Err(ToaruError::UnknownClient(id))
+        };
+        // cover the screen the window is on
+        let scr = self
+            .screens
+            .iter()
+            .find(|s| s.active.as_ref() == Some(&ws.name))
+            .unwrap_or_else(|| self.screens.focused().unwrap());
+        ws.set_fullscreen(id, should_fullscreen, &self.conn, scr, &self.config);
+        Ok(())
     }
 
-    fn toggle_urgency(&mut self, _id: XWindowID) -> Result<()> {
-        //todo
+    /// Kills clients that were asked to close but have not done so
+    /// within the timeout, and if that doesn't work either, signals
+    /// their process as a last resort.
+    fn escalate_closing(&mut self) {
+        let now = Instant::now();
+        let timeout = self.config.close_timeout();
+        for (id, since, killed) in std::mem::take(&mut self.closing) {
+            if !self.desktop.is_managing(id) {
+                continue
+            }
+            if now.duration_since(since) < timeout {
+                self.closing.push((id, since, killed));
+            } else if !killed {
+                warn!("Client {} did not close in time, killing it", id);
+                handle_err!(self.conn.kill_client(id), self);
+                self.closing.push((id, now, true));
+            } else {
+                handle_err!(self.signal_client(id), self);
+            }
+        }
+    }
+
+    /// Sends SIGKILL to the process owning a client, using `_NET_WM_PID`.
+    ///
+    /// This is only done if the client runs on this machine,
+    /// as given by `WM_CLIENT_MACHINE`.
+    fn signal_client(&self, id: XWindowID) -> Result<()> {
+        use nix::sys::signal::{kill, Signal};
+        use nix::unistd::{gethostname, Pid};
+
+        let Some(pid) = self.conn.get_wm_pid(id) else {
+            return Err(ToaruError::OtherError(format!("client {} has no _NET_WM_PID", id)))
+        };
+        let mut buf = [0u8; 256];
+        let host = gethostname(&mut buf)
+            .map_err(|e| ToaruError::OtherError(format!("could not get hostname: {}", e)))?;
+        if self.conn.get_wm_client_machine(id).as_deref() != host.to_str().ok() {
+            return Err(ToaruError::OtherError(format!(
+                "client {} is not running on this machine",
+                id
+            )))
+        }
+
+        warn!("Killing process {} of client {}", pid, id);
+        kill(Pid::from_raw(pid as i32), Signal::SIGKILL)
+            .map_err(|e| ToaruError::OtherError(format!("could not kill process {}: {}", pid, e)))
+    }
+
+    /// Re-reads the size hints of a client and resizes it to match.
+    ///
+    /// Tiled clients are only resized if their layout honours size hints.
+    fn update_size_hints(&mut self, id: XWindowID) -> Result<()> {
+        let Some((ws, _)) = self.desktop.retrieve_mut(id) else {
+            return Err(ToaruError::UnknownClient(id))
+        };
+        let floating = ws.is_floating();
+        let client = ws.windows.lookup_mut(id).unwrap();
+        client.update_size_hints(&self.conn);
+        if client.is_fullscreen() {
+            return Ok(())
+        }
+
+        if client.is_off_layout() || floating {
+            let geom = client.geometry();
+            client.set_and_update_geometry(&self.conn, geom);
+        } else if let Some(scr) = self
+            .screens
+            .iter()
+            .find(|s| s.active.as_ref() == Some(&ws.name))
+        {
+            ws.relayout(&self.conn, scr, &self.config);
+        }
         Ok(())
     }
 
+    /// Sets or clears the urgent state of a client.
+    ///
+    /// The focused client is never marked urgent.
+    fn set_urgency(&mut self, id: XWindowID, urgent: bool) -> Result<()> {
+        let focused = self.focused_client_id();
+        let Some((ws, _)) = self.desktop.retrieve_mut(id) else {
+            return Err(ToaruError::UnknownClient(id))
+        };
+        let client = ws.windows.lookup_mut(id).unwrap();
+        if client.is_urgent() == urgent || (urgent && focused == Some(id)) {
+            return Ok(())
+        }
+        debug!("Setting urgency of window {} to {}", id, urgent);
+        client.set_urgent(&self.conn, &self.config, urgent);
+
+        self.urgent.retain(|w| *w != id);
+        if urgent {
+            self.urgent.push(id);
+        }
+        Ok(())
+    }
+
+    /// Re-queries the connected screens and updates our own to match.
+    ///
+    /// Screens are matched up by index. Workspaces on screens that
+    /// no longer exist are moved to the first screen, and new screens
+    /// are given a workspace taken from a screen that has more than one.
+    #[cfg_attr(debug_assertions, instrument(level = "debug", skip(self)))]
     fn screen_reconfigure(&mut self) -> Result<()> {
-        todo!()
+        let old: Vec<Screen> = self.screens.iter().cloned().collect();
+        let mut new = self.conn.all_outputs()?;
+        debug!("Reconfiguring screens to {:?}", new);
+
+        // keep the workspaces of screens that are still around
+        let prev = match_screens(&old, &new);
+        for (scr, prev) in new.iter_mut().zip(&prev) {
+            if let Some(prev) = prev.map(|i| &old[i]) {
+                scr.wix = prev.wix.clone();
+                scr.active = prev.active.clone();
+            }
+        }
+
+        // all_outputs never returns an empty set of screens
+        let orphaned = old
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !prev.contains(&Some(*i)))
+            .flat_map(|(_, s)| s.wix.iter().cloned())
+            .collect::<Vec<_>>();
+        new[0].wix.extend(orphaned);
+        distribute_workspaces(&mut new);
+
+        // hide any workspaces that are no longer shown
+        for name in old.iter().filter_map(|s| s.active_workspace()) {
+            if new.iter().any(|s| s.active_workspace() == Some(name)) {
+                continue
+            }
+            if let Some(ws) = self.desktop.find_mut(name) {
+                ws.deactivate(&self.conn);
+            }
+        }
+
+        let focused = self
+            .screens
+            .focused_idx()
+            .and_then(|f| prev.iter().position(|p| *p == Some(f)))
+            .unwrap_or(0);
+        self.screens = Ring::from_iter(new);
+        self.set_screen_focus(focused);
+        self.apply_struts();
+        self.activate_visible();
+
+        let new: Vec<Screen> = self.screens.iter().cloned().collect();
+        let mut hooks = std::mem::take(&mut self.screen_hooks);
+        for hook in hooks.iter_mut() {
+            hook(self, &old, &new);
+        }
+        self.screen_hooks = hooks;
+
+        Ok(())
+    }
+
+    /// Returns the index of the screen showing the workspace `name`, if any.
+    fn screen_showing(&self, name: &str) -> Option<usize> {
+        self.screens
+            .index(Selector::Condition(&|s| s.active_workspace() == Some(name)))
+    }
+
+    /// Returns the index of the screen the workspace `name` belongs to, if any.
+    fn screen_owning(&self, name: &str) -> Option<usize> {
+        self.screens
+            .index(Selector::Condition(&|s| s.wix.iter().any(|ws| ws == name)))
     }
 
-    fn focus_screen(&mut self, idx: usize) {
+    /// Sets the focused screen, making the workspace it shows current.
+    fn set_screen_focus(&mut self, idx: usize) {
         self.screens.set_focused(idx);
+
+        let Some(ws_idx) = self.screens[idx]
+            .active_workspace()
+            .and_then(|name| self.desktop.index_of(name))
+        else {
+            return
+        };
+        self.desktop.set_current(ws_idx);
+        handle_err!(
+            self.conn.set_property(
+                self.root.id,
+                Atom::NetCurrentDesktop.as_ref(),
+                Property::Cardinal(ws_idx as u32),
+            ),
+            self
+        );
+    }
+
+    /// Shows the workspace `name` on the screen at `idx`, hiding the
+    /// workspace that was previously shown there.
+    fn show_on_screen(&mut self, name: &str, idx: usize) {
+        let scr = &mut self.screens[idx];
+        if scr.active_workspace() == Some(name) {
+            return
+        }
+        let prev = scr.active.replace(name.into());
+        if let Some(ws) = prev.and_then(|prev| self.desktop.find_mut(&prev)) {
+            ws.deactivate(&self.conn);
+        }
+        if let Some(ws) = self.desktop.find_mut(name) {
+            ws.activate(&self.conn, &self.screens[idx], &self.config);
+        }
+    }
+
+    /// Shows a workspace according to the multi-screen policy.
+    fn show_workspace(&mut self, name: &str) -> Result<()> {
+        if self.desktop.index_of(name).is_none() {
+            return Err(ToaruError::UnknownWorkspace(name.into()));
+        }
+
+        let focused = self.screens.focused_idx().unwrap();
+        let target = self
+            .screen_showing(name)
+            .or_else(|| self.screen_owning(name))
+            .unwrap_or(focused);
+
+        self.show_on_screen(name, target);
+        if target != focused {
+            self.focus_screen(target);
+        } else {
+            self.set_screen_focus(target);
+        }
+        Ok(())
+    }
+
+    /// Lays out and maps the workspaces shown on every screen.
+    ///
+    /// The workspace on the focused screen is activated last,
+    /// so that it ends up with the input focus.
+    fn activate_visible(&mut self) {
+        let focused = self.screens.focused_idx();
+        let order: Vec<usize> = (0..self.screens.len())
+            .filter(|&i| Some(i) != focused)
+            .chain(focused)
+            .collect();
+
+        for idx in order {
+            let Some(name) = self.screens[idx].active.clone() else {
+                continue
+            };
+            if let Some(ws) = self.desktop.find_mut(&name) {
+                ws.activate(&self.conn, &self.screens[idx], &self.config);
+            }
+        }
+    }
+
+    /// Lays out a window just sent to workspace `name`, if that
+    /// workspace is shown on a screen.
+    fn display_sent_window(&mut self, id: XWindowID, name: &str) {
+        let Some(idx) = self.screen_showing(name) else {
+            return
+        };
+        let Some(ws) = self.desktop.find_mut(name) else {
+            return
+        };
+        if ws.windows.lookup(id).is_none() {
+            return
+        }
+        // its dialogs were sent along with it
+        for sent in [id].into_iter().chain(ws.transients_of(id)) {
+            ws.windows.lookup_mut(sent).unwrap().map(&self.conn);
+        }
+        ws.relayout(&self.conn, &self.screens[idx], &self.config);
     }
 
     fn handle_error(&mut self, err: XError, _evt: XEvent) {
@@ -927,3 +2101,1098 @@ where
             .finish()
     }
 }
+
+/// Finds the screen in `old` that each screen in `new` used to be.
+///
+/// Screens are matched by their RandR output, then by their geometry,
+/// and only then by their index, since the indices of screens change
+/// when one before them is disconnected.
+fn match_screens(old: &[Screen], new: &[Screen]) -> Vec<Option<usize>> {
+    let passes: [fn(&Screen, &Screen) -> bool; 3] = [
+        |o, n| o.output.is_some() && o.output == n.output,
+        |o, n| o.true_geom == n.true_geom,
+        |o, n| o.idx == n.idx,
+    ];
+
+    let mut prev: Vec<Option<usize>> = vec![None; new.len()];
+    for matches in passes {
+        for (i, scr) in new.iter().enumerate() {
+            if prev[i].is_none() {
+                prev[i] = (0..old.len())
+                    .find(|j| !prev.contains(&Some(*j)) && matches(&old[*j], scr));
+            }
+        }
+    }
+    prev
+}
+
+/// Makes sure every screen with workspaces has one that it shows.
+///
+/// Screens without any workspaces are given one from a screen
+/// that has more than one, if there is any to spare.
+fn distribute_workspaces(screens: &mut [Screen]) {
+    for i in 0..screens.len() {
+        if !screens[i].wix.is_empty() {
+            continue
+        }
+        let Some(donor) = screens.iter().position(|s| s.wix.len() > 1) else {
+            break
+        };
+        let donor_active = screens[donor].active.clone();
+        let Some(pos) = screens[donor]
+            .wix
+            .iter()
+            .rposition(|ws| Some(ws) != donor_active.as_ref())
+        else {
+            continue
+        };
+        let name = screens[donor].wix.remove(pos);
+        screens[i].wix.push(name);
+    }
+
+    for i in 0..screens.len() {
+        if screens[i].active.is_some() {
+            continue
+        }
+        let shown: Vec<String> = screens.iter().filter_map(|s| s.active.clone()).collect();
+        screens[i].active = screens[i]
+            .wix
+            .iter()
+            .find(|ws| !shown.contains(ws))
+            .cloned();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::backend::x::{
+        core::{StackMode, XCore},
+        dummy::DummyConn,
+        event::{ClientMessageData, ClientMessageEvent, PropertyEvent},
+        property::{WmHints, WmHintsFlags},
+        WmSizeHints,
+    };
+    use crate::types::BorderStyle;
+    use crate::ToaruWM;
+
+    use std::time::Duration;
+
+    fn wm() -> ToaruWM<DummyConn> {
+        let conn = DummyConn::new(XWindow::with_data(Xid(1), Geometry::new(0, 0, 1080, 1920)));
+        let mut wm = WindowManager::new(conn, ToaruConfig::default()).unwrap();
+        wm.register(Vec::new());
+        wm
+    }
+
+    #[test]
+    fn test_run_maps_windows() {
+        let mut wm = wm();
+        let one = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        let two = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        wm.conn.request_map(one);
+        wm.conn.request_map(two);
+
+        // the dummy connection errors out once it runs out of events
+        assert!(wm.run(Keybinds::new(), Mousebinds::new()).is_err());
+
+        assert_eq!(wm.desktop.current().windows.len(), 2);
+        assert!(wm.conn.is_mapped(one) && wm.conn.is_mapped(two));
+        assert_eq!(wm.conn.focused_window(), two);
+
+        let g1 = wm.conn.get_geometry(one).unwrap();
+        let g2 = wm.conn.get_geometry(two).unwrap();
+        assert!(g1.x != g2.x || g1.y != g2.y);
+    }
+
+    #[test]
+    fn test_main_count() {
+        use crate::layouts::update::{DecMain, IncMain};
+        use crate::layouts::{DynamicTiled, Layout};
+
+        let mut wm = wm();
+        let windows: Vec<_> = (0..3)
+            .map(|_| wm.conn.add_window(Geometry::new(0, 0, 100, 100)))
+            .collect();
+        for id in &windows {
+            wm.conn.request_map(*id);
+        }
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        // the number of windows sharing each column
+        let columns = |wm: &ToaruWM<DummyConn>| {
+            let mut xs: Vec<_> = windows
+                .iter()
+                .map(|id| wm.conn.get_geometry(*id).unwrap().x)
+                .collect();
+            xs.sort();
+            let mut counts = Vec::new();
+            for (i, x) in xs.iter().enumerate() {
+                if i == 0 || xs[i - 1] != *x {
+                    counts.push(0);
+                }
+                *counts.last_mut().unwrap() += 1;
+            }
+            counts
+        };
+        assert_eq!(columns(&wm), [1, 2]);
+
+        wm.update_current_layout(IncMain(1));
+        assert_eq!(columns(&wm), [2, 1]);
+
+        wm.update_current_layout(IncMain(1));
+        assert_eq!(columns(&wm), [3]);
+
+        wm.update_current_layout(DecMain(5));
+        assert_eq!(columns(&wm), [3]);
+
+        // the main count is kept per workspace, and across restarts
+        wm.update_current_layout(IncMain(2));
+        let main_count = |wm: &ToaruWM<DummyConn>, name: &str| {
+            let ws = wm.desktop.find(name).unwrap();
+            let (_, layout) = ws.layouts.element_by(|l| l.name() == "DTiled").unwrap();
+            let restored = DynamicTiled::new(0.5, 0);
+            restored.restore_params(&layout.save_params().unwrap());
+            restored.main_count()
+        };
+        assert_eq!(main_count(&wm, "1"), 2);
+        assert_eq!(main_count(&wm, "2"), 1);
+    }
+
+    #[test]
+    fn test_layouts() {
+        use crate::layouts::modifiers::SmartBorders;
+        use crate::layouts::update::Rotate;
+        use crate::layouts::{Bsp, Grid, Layout};
+
+        let mut wm = wm();
+        wm.desktop.current_mut().layouts.append(Box::new(Bsp::new()));
+        wm.switch_layout("BSP");
+
+        let windows: Vec<_> = (0..3)
+            .map(|_| wm.conn.add_window(Geometry::new(0, 0, 100, 100)))
+            .collect();
+        let (a, b, c) = (windows[0], windows[1], windows[2]);
+        for id in &windows {
+            wm.conn.request_map(*id);
+        }
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        let bw = wm.config.border_px() as i32;
+        let tile = |x, y, h, w| Geometry::new(x, y, h - 2 * bw, w - 2 * bw);
+        let geom = |wm: &ToaruWM<DummyConn>, id| wm.conn.get_geometry(id).unwrap();
+
+        // each window splits the focused one
+        assert_eq!(geom(&wm, a), tile(0, 0, 1080, 960));
+        assert_eq!(geom(&wm, b), tile(960, 0, 540, 960));
+        assert_eq!(geom(&wm, c), tile(960, 540, 540, 960));
+
+        // updates change the split holding the focused window
+        wm.update_current_layout(Rotate);
+        assert_eq!(geom(&wm, c), tile(1440, 0, 1080, 480));
+
+        // the sibling of a window taken off the layout gets its space
+        wm.toggle_focused_state();
+        assert_eq!(geom(&wm, b), tile(960, 0, 1080, 960));
+
+        // the tree is kept across restarts
+        let layout = wm.desktop.current().layouts.focused().unwrap();
+        let saved = layout.save_params().unwrap();
+        let restored = Bsp::new();
+        restored.restore_params(&saved);
+        assert_eq!(restored.save_params(), Some(saved));
+
+        // a lone window drops its border until the layout is switched
+        let border = |wm: &ToaruWM<DummyConn>, id| wm.conn.window(id).unwrap().border_width;
+        wm.desktop.current_mut().layouts.append(Box::new(SmartBorders::new(Grid::new().boxed())));
+        wm.switch_layout("Grid");
+        assert_eq!(border(&wm, a), bw as u32);
+
+        wm.desktop.current_mut().focus_window(b, &wm.conn, &wm.config);
+        wm.toggle_focused_state();
+        assert_eq!(geom(&wm, a), Geometry::new(0, 0, 1080, 1920));
+        assert_eq!(border(&wm, a), 0);
+        assert_eq!(border(&wm, b), bw as u32);
+
+        // borders that stay the same are not set again
+        let configures = |wm: &ToaruWM<DummyConn>| {
+            let window = |id| wm.conn.window(id).unwrap();
+            windows.iter().map(|id| window(*id).border_configures).collect::<Vec<_>>()
+        };
+        let before = configures(&wm);
+        wm.switch_layout("Grid");
+        assert_eq!(configures(&wm), before);
+
+        wm.switch_layout("BSP");
+        assert_eq!(geom(&wm, a), tile(0, 0, 1080, 1920));
+        assert_eq!(border(&wm, a), bw as u32);
+    }
+
+    #[test]
+    fn test_client_lists() {
+        let mut wm = wm();
+        let root = wm.root.id;
+        let windows: Vec<_> = (0..3)
+            .map(|_| wm.conn.add_window(Geometry::new(0, 0, 100, 100)))
+            .collect();
+        for id in &windows {
+            wm.conn.request_map(*id);
+        }
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        let list = |wm: &ToaruWM<DummyConn>, atom: Atom| {
+            match wm.conn.get_property(atom.as_ref(), root).unwrap() {
+                Some(Property::Window(ids)) => ids,
+                p => panic!("unexpected property {:?}", p),
+            }
+        };
+        assert_eq!(list(&wm, Atom::NetClientList), windows);
+        let mut stacking = wm.conn.stacking_order();
+        stacking.retain(|id| windows.contains(id));
+        assert_eq!(list(&wm, Atom::NetClientListStacking), stacking);
+
+        // raising a window changes only the stacking order
+        let raise = ClientConfig::StackingMode(StackMode::Above(None));
+        wm.conn.configure_window(windows[0], &[raise]).unwrap();
+        wm.conn.destroy_window(windows[1]).unwrap();
+        wm.conn.add_event(XEvent::DestroyNotify(windows[1]));
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        assert_eq!(list(&wm, Atom::NetClientList), [windows[0], windows[2]]);
+        assert_eq!(list(&wm, Atom::NetClientListStacking), [windows[2], windows[0]]);
+        assert!(!wm.desktop.is_managing(windows[1]));
+    }
+
+    #[test]
+    fn test_adopt_existing_windows() {
+        let mut wm = wm();
+        let geom = Geometry::new(0, 0, 100, 100);
+        let mapped = wm.conn.add_window(geom);
+        let elsewhere = wm.conn.add_window(geom);
+        let ovrd = wm.conn.add_override_redirect(geom);
+        let unmapped = wm.conn.add_window(geom);
+        for id in [mapped, elsewhere, ovrd] {
+            wm.conn.map_window(id).unwrap();
+        }
+        wm.conn
+            .set_property(elsewhere, Atom::NetWmDesktop.as_ref(), Property::Cardinal(1))
+            .unwrap();
+
+        let adopted = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counter = adopted.clone();
+        wm.add_adopt_hook(move |_, _| counter.set(counter.get() + 1));
+
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        assert_eq!(adopted.get(), 2);
+        assert!(wm.desktop.current().contains_window(mapped));
+        assert!(wm.desktop.workspaces[1].contains_window(elsewhere));
+        assert!(!wm.conn.is_mapped(elsewhere));
+        assert!(!wm.desktop.is_managing(ovrd));
+        assert!(!wm.desktop.is_managing(unmapped));
+    }
+
+    #[test]
+    fn test_restore_saved_state() {
+        let mut wm = wm();
+        let geom = Geometry::new(0, 0, 100, 100);
+        let (one, two, three) = (
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+        );
+        for id in [one, two, three] {
+            wm.conn.request_map(id);
+        }
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        // float one window and move it, and send another to a different workspace
+        wm.toggle_focused_state();
+        wm.move_window(20, Cardinal::Right);
+        let floating = wm.conn.get_geometry(three).unwrap();
+        wm.desktop.current_mut().focus_window(one, &wm.conn, &wm.config);
+        wm.send_focused_to("2");
+        wm.switch_layout("Floating");
+        let saved = SavedState::from_desktop(&wm.desktop, &wm.screens);
+
+        // hand the server over to a fresh window manager
+        let WindowManager { conn, .. } = wm;
+        let mut wm = WindowManager::new(conn, ToaruConfig::default()).unwrap();
+        wm.adopt_existing_windows(Some(saved)).unwrap();
+
+        let current = wm.desktop.current();
+        assert_eq!(current.layout(), "Floating");
+        assert!(current.contains_window(two) && current.contains_window(three));
+        assert!(current.windows.lookup(three).unwrap().is_off_layout());
+        assert_eq!(wm.conn.get_geometry(three).unwrap(), floating);
+        assert!(wm.desktop.find("2").unwrap().contains_window(one));
+        assert!(!wm.conn.is_mapped(one));
+    }
+
+    #[test]
+    fn test_restore_reapplies_rules() {
+        use super::rules::{Condition, Pattern, WindowRule};
+
+        let rules = vec![WindowRule::new()
+            .when(Condition::Class(Pattern::exact("Bordered")))
+            .then(RuleAction::BorderPx(5))
+            .then(RuleAction::NoFocus)];
+        let mut wm = wm();
+        wm.config.rules = rules.clone();
+        let geom = Geometry::new(0, 0, 100, 100);
+        let (plain, bordered) = (wm.conn.add_window(geom), wm.conn.add_window(geom));
+        let class = Property::String(vec!["bordered".into(), "Bordered".into()]);
+        wm.conn.set_property(bordered, Atom::WmClass.as_ref(), class).unwrap();
+        wm.conn.request_map(plain);
+        wm.conn.request_map(bordered);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        let saved = SavedState::from_desktop(&wm.desktop, &wm.screens);
+
+        let WindowManager { conn, .. } = wm;
+        let mut wm = WindowManager::new(conn, ToaruConfig::default()).unwrap();
+        wm.config.rules = rules;
+        wm.adopt_existing_windows(Some(saved)).unwrap();
+
+        let current = wm.desktop.current();
+        let client = current.windows.lookup(bordered).unwrap();
+        assert_eq!(client.border_px(), Some(5));
+        assert_eq!(wm.conn.window(bordered).unwrap().border_width, 5);
+        assert!(!client.can_focus(&wm.conn));
+        let client = current.windows.lookup(plain).unwrap();
+        assert_eq!(wm.conn.window(plain).unwrap().border_width, wm.config.border_px());
+        assert!(client.can_focus(&wm.conn));
+    }
+
+    #[test]
+    fn test_goto_workspace() {
+        let mut wm = wm();
+        let win = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        wm.conn.request_map(win);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        wm.goto_workspace("2");
+        assert_eq!(wm.desktop.current().name, "2");
+        assert!(!wm.conn.is_mapped(win));
+
+        wm.goto_workspace("1");
+        assert!(wm.conn.is_mapped(win));
+        assert_eq!(wm.conn.focused_window(), win);
+    }
+
+    #[test]
+    fn test_configure_request() {
+        use super::rules::{Condition, Pattern, WindowRule};
+
+        let mut wm = wm();
+        wm.config.rules = vec![WindowRule::new()
+            .when(Condition::Class(Pattern::exact("Bordered")))
+            .then(RuleAction::BorderPx(5))];
+        let geom = Geometry::new(0, 0, 100, 100);
+        let (tiled, bordered, floating, unmanaged) = (
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+        );
+        let class = Property::String(vec!["bordered".into(), "Bordered".into()]);
+        wm.conn.set_property(bordered, Atom::WmClass.as_ref(), class).unwrap();
+        wm.conn.request_map(tiled);
+        wm.conn.request_map(bordered);
+        wm.conn.request_map(floating);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        wm.toggle_focused_state();
+
+        let hints = WmSizeHints {
+            min_size: Some((300, 200)),
+            ..WmSizeHints::new()
+        };
+        wm.conn
+            .set_property(floating, Atom::WmNormalHints.as_ref(), Property::WMSizeHints(hints))
+            .unwrap();
+
+        let request = |id, x, y, height, width| ConfigureRequestData {
+            id,
+            parent: wm.root.id,
+            sibling: None,
+            x: Some(x),
+            y: Some(y),
+            height: Some(height),
+            width: Some(width),
+            stack_mode: None,
+            is_root: false,
+        };
+        let requests = [
+            request(tiled, 10, 10, 50, 50),
+            request(bordered, 10, 10, 50, 50),
+            request(floating, 20, 30, 50, 50),
+            request(unmanaged, 40, 40, 60, 80),
+        ];
+        for data in requests {
+            wm.configure_client(data).unwrap();
+        }
+
+        // tiled windows stay put, and are told so along with their border
+        let geom_of = |id| wm.desktop.current().windows.lookup(id).unwrap().geometry();
+        let (tiled_geom, bordered_geom) = (geom_of(tiled), geom_of(bordered));
+        assert_eq!(wm.conn.get_geometry(tiled).unwrap(), tiled_geom);
+        assert_eq!(
+            wm.conn.sent_configure_notifies(),
+            vec![
+                (tiled, tiled_geom, wm.config.border_px()),
+                (bordered, bordered_geom, 5),
+            ]
+        );
+
+        // floating windows are moved, but respect their size hints
+        assert_eq!(
+            wm.conn.get_geometry(floating).unwrap(),
+            Geometry::new(20, 30, 200, 300)
+        );
+        assert_eq!(
+            wm.desktop.current().windows.lookup(floating).unwrap().geometry(),
+            Geometry::new(20, 30, 200, 300)
+        );
+        assert_eq!(
+            wm.conn.get_geometry(unmanaged).unwrap(),
+            Geometry::new(40, 40, 60, 80)
+        );
+    }
+
+    #[test]
+    fn test_size_hints() {
+        let mut wm = wm();
+        let id = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        let hints = WmSizeHints {
+            resize_inc: Some((10, 20)),
+            base_size: Some((4, 6)),
+            ..WmSizeHints::new()
+        };
+        wm.conn
+            .set_property(id, Atom::WmNormalHints.as_ref(), Property::WMSizeHints(hints))
+            .unwrap();
+        wm.conn.request_map(id);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        let geom = |wm: &ToaruWM<DummyConn>| wm.conn.get_geometry(id).unwrap();
+
+        // the tiled layout fills its space regardless of the hints
+        assert_eq!(geom(&wm), Geometry::new(0, 0, 1076, 1916));
+
+        // but floating windows are snapped to their increments
+        wm.toggle_focused_state();
+        wm.resize_window(15, Cardinal::Right);
+        assert_eq!(geom(&wm), Geometry::new(0, 0, 1066, 1924));
+
+        // pointer movement builds up until it makes a full increment
+        wm.selected = Some(id);
+        wm.last_mouse_pos = Point { x: 0, y: 0 };
+        wm.resize_window_ptr(Point { x: 7, y: 25 });
+        assert_eq!(geom(&wm), Geometry::new(0, 0, 1086, 1924));
+        wm.resize_window_ptr(Point { x: 14, y: 25 });
+        assert_eq!(geom(&wm), Geometry::new(0, 0, 1086, 1934));
+        assert_eq!(wm.last_mouse_pos, Point { x: 10, y: 20 });
+    }
+
+    #[test]
+    fn test_transients() {
+        let mut wm = wm();
+        let parent = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        wm.conn.request_map(parent);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        let dialog = wm.conn.add_window(Geometry::new(0, 0, 100, 200));
+        wm.conn
+            .set_property(dialog, Atom::WmTransientFor.as_ref(), Property::Window(vec![parent]))
+            .unwrap();
+        wm.conn.request_map(dialog);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        // dialogs float centred over their parent
+        let client = wm.desktop.current().windows.lookup(dialog).unwrap();
+        assert!(client.is_off_layout());
+        assert_eq!(client.geometry(), Geometry::new(858, 488, 100, 200));
+
+        // and stay above it when it is focused
+        wm.desktop
+            .current_mut()
+            .focus_window(parent, &wm.conn, &wm.config);
+        let order = wm.conn.stacking_order();
+        let pos = |id| order.iter().position(|w| *w == id).unwrap();
+        assert!(pos(dialog) > pos(parent));
+
+        // they follow their parent to other workspaces
+        wm.send_focused_to("2");
+        assert!(wm.desktop.find("2").unwrap().contains_window(dialog));
+        assert!(!wm.conn.is_mapped(dialog));
+
+        // dialogs for windows on hidden workspaces go there too
+        let hidden = wm.conn.add_window(Geometry::new(0, 0, 100, 200));
+        wm.conn
+            .set_property(hidden, Atom::WmTransientFor.as_ref(), Property::Window(vec![parent]))
+            .unwrap();
+        wm.conn.request_map(hidden);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        assert!(wm.desktop.find("2").unwrap().contains_window(hidden));
+        assert!(!wm.conn.is_mapped(hidden));
+
+        // and are left as normal floating windows when it goes away
+        wm.destroy_client(parent).unwrap();
+        let ws = wm.desktop.find("2").unwrap();
+        assert_eq!(ws.windows.lookup(dialog).unwrap().transient_for(), None);
+        assert_eq!(ws.windows.lookup(hidden).unwrap().transient_for(), None);
+    }
+
+    #[test]
+    fn test_fullscreen() {
+        let mut wm = wm();
+        let geom = Geometry::new(0, 0, 100, 100);
+        let (one, two) = (wm.conn.add_window(geom), wm.conn.add_window(geom));
+        wm.conn.request_map(one);
+        wm.conn.request_map(two);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        let tiled = wm.conn.get_geometry(two).unwrap();
+        let other = wm.conn.get_geometry(one).unwrap();
+
+        wm.set_fullscreen(two, true).unwrap();
+        let screen = wm.screens.focused().unwrap().true_geom();
+        assert_eq!(wm.conn.get_geometry(two).unwrap(), screen);
+        assert_eq!(wm.conn.window(two).unwrap().border_width, 0);
+        assert_eq!(wm.conn.stacking_order().last(), Some(&two));
+        // the remaining window takes up the space left behind
+        assert!(wm.conn.get_geometry(one).unwrap().width > other.width);
+        assert_eq!(
+            wm.conn.get_window_states(two).unwrap(),
+            vec![Atom::NetWmStateFullscreen.to_string()]
+        );
+
+        wm.toggle_focused_fullscreen();
+        let client = wm.desktop.current().windows.lookup(two).unwrap();
+        assert!(!client.is_fullscreen() && !client.is_off_layout());
+        assert_eq!(wm.conn.get_geometry(two).unwrap(), tiled);
+        assert_eq!(wm.conn.window(two).unwrap().border_width, wm.config.border_px());
+        assert!(wm.conn.get_window_states(two).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_focus_models() {
+        let mut wm = wm();
+        let geom = Geometry::new(0, 0, 100, 100);
+        let (passive, no_input, global) = (
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+        );
+        let hints = WmHints {
+            flags: WmHintsFlags::INPUT_HINT,
+            accepts_input: false,
+            ..WmHints::new()
+        };
+        for id in [no_input, global] {
+            wm.conn
+                .set_property(id, Atom::WmHints.as_ref(), Property::WMHints(hints))
+                .unwrap();
+        }
+        wm.conn
+            .set_property(
+                global,
+                Atom::WmProtocols.as_ref(),
+                Property::Atom(vec![Atom::WmTakeFocus.to_string()]),
+            )
+            .unwrap();
+
+        wm.conn.add_event(XEvent::PropertyNotify(PropertyEvent {
+            id: wm.root.id,
+            atom: wm.conn.atom(Atom::WmName.as_ref()).unwrap(),
+            time: 1234,
+            deleted: false,
+        }));
+        for id in [passive, no_input, global] {
+            wm.conn.request_map(id);
+        }
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        // no input windows are never focused
+        let focused = |wm: &ToaruWM<DummyConn>| wm.focused_client_id();
+        wm.desktop
+            .current_mut()
+            .focus_window(passive, &wm.conn, &wm.config);
+        wm.desktop
+            .current_mut()
+            .focus_window(no_input, &wm.conn, &wm.config);
+        assert_eq!(focused(&wm), Some(passive));
+        assert_eq!(wm.conn.focused_window(), passive);
+
+        // globally active windows are asked to take the focus themselves
+        wm.desktop
+            .current_mut()
+            .focus_window(global, &wm.conn, &wm.config);
+        assert_eq!(focused(&wm), Some(global));
+        assert_eq!(wm.conn.focused_window(), passive);
+
+        let take_focus = *wm.conn.atom(Atom::WmTakeFocus.as_ref()).unwrap();
+        let (id, msg) = wm.conn.sent_messages().pop().unwrap();
+        assert_eq!(id, global);
+        assert_eq!(msg.type_, wm.conn.atom(Atom::WmProtocols.as_ref()).unwrap());
+        assert!(matches!(msg.data, ClientMessageData::U32([a, 1234, ..]) if a == take_focus));
+
+        // cycling the focus skips them
+        wm.cycle_focus(Direction::Forward);
+        assert_eq!(focused(&wm), Some(passive));
+
+        // and leaves it alone if nothing else can take it
+        wm.goto_workspace("2");
+        let others = [wm.conn.add_window(geom), wm.conn.add_window(geom)];
+        for id in others {
+            wm.conn
+                .set_property(id, Atom::WmHints.as_ref(), Property::WMHints(hints))
+                .unwrap();
+            wm.conn.request_map(id);
+        }
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        let before = focused(&wm);
+        wm.cycle_focus(Direction::Forward);
+        assert_eq!(focused(&wm), before);
+        let focused_colour = wm.config.border_style(BorderStyle::Focused);
+        for id in others {
+            assert_ne!(wm.conn.window(id).unwrap().border_colour, Some(focused_colour));
+        }
+    }
+
+    #[test]
+    fn test_close_window() {
+        let mut wm = wm();
+        let geom = Geometry::new(0, 0, 100, 100);
+        let (polite, rude, stuck) = (
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+        );
+        for id in [polite, stuck] {
+            wm.conn
+                .set_property(
+                    id,
+                    Atom::WmProtocols.as_ref(),
+                    Property::Atom(vec![Atom::WmDeleteWindow.to_string()]),
+                )
+                .unwrap();
+        }
+        for id in [polite, rude, stuck] {
+            wm.conn.request_map(id);
+        }
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        let focus = |wm: &mut ToaruWM<DummyConn>, id| {
+            wm.desktop.current_mut().focus_window(id, &wm.conn, &wm.config)
+        };
+
+        // clients without WM_DELETE_WINDOW are killed outright
+        focus(&mut wm, rude);
+        wm.close_focused_window();
+        assert!(!wm.conn.exists(rude));
+
+        // others are asked nicely, and killed if they don't listen
+        focus(&mut wm, polite);
+        wm.close_focused_window();
+        let (id, msg) = wm.conn.sent_messages().pop().unwrap();
+        assert_eq!(id, polite);
+        let delete = *wm.conn.atom(Atom::WmDeleteWindow.as_ref()).unwrap();
+        assert!(matches!(msg.data, ClientMessageData::U32([a, ..]) if a == delete));
+        wm.escalate_closing();
+        assert!(wm.conn.exists(polite));
+
+        // even if nothing else happens in the meantime
+        wm.config.close_timeout = Duration::from_millis(10);
+        assert_eq!(wm.conn.pending_events(), 0);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        assert!(!wm.conn.exists(polite));
+
+        // or killed without asking
+        focus(&mut wm, stuck);
+        let sent = wm.conn.sent_messages().len();
+        wm.kill_focused_window();
+        assert!(!wm.conn.exists(stuck));
+        assert_eq!(wm.conn.sent_messages().len(), sent);
+    }
+
+    #[test]
+    fn test_active_window() {
+        let mut wm = wm();
+        let geom = Geometry::new(0, 0, 100, 100);
+        let (one, two) = (wm.conn.add_window(geom), wm.conn.add_window(geom));
+        wm.conn.request_map(one);
+        wm.conn.request_map(two);
+
+        let message = |wm: &ToaruWM<DummyConn>, atom: Atom, data| {
+            let type_ = wm.conn.atom(atom.as_ref()).unwrap();
+            wm.conn.add_event(XEvent::ClientMessage(ClientMessageEvent {
+                window: two,
+                data: ClientMessageData::U32(data),
+                type_,
+            }));
+        };
+        let root = wm.root.id;
+        let active = |wm: &ToaruWM<DummyConn>| {
+            match wm.conn.get_property(Atom::NetActiveWindow.as_ref(), root).unwrap() {
+                Some(Property::Window(ids)) => ids,
+                p => panic!("unexpected property {:?}", p),
+            }
+        };
+
+        message(&wm, Atom::NetWmDesktop, [1, 0, 0, 0, 0]);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        assert_eq!(active(&wm), [one]);
+
+        // an application can't steal the focus by default
+        message(&wm, Atom::NetActiveWindow, [1, 0, 0, 0, 0]);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        assert_eq!(wm.desktop.current_name(), "1");
+        assert!(wm.desktop.find("2").unwrap().windows.lookup(two).unwrap().is_urgent());
+
+        // but a pager can
+        message(&wm, Atom::NetActiveWindow, [2, 0, 0, 0, 0]);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        assert_eq!(wm.desktop.current_name(), "2");
+        assert_eq!(wm.conn.focused_window(), two);
+        assert_eq!(active(&wm), [two]);
+    }
+
+    #[test]
+    fn test_window_rules() {
+        use super::rules::{Condition, Pattern, WindowRule};
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let mut wm = wm();
+        let float_geom = Geometry::new(10, 20, 300, 400);
+        wm.config.rules = vec![
+            WindowRule::new()
+                .when(Condition::Class(Pattern::exact("Gimp")))
+                .then(RuleAction::Workspace("2".into()))
+                .then(RuleAction::BorderPx(5)),
+            WindowRule::new()
+                .when(Condition::Title(Pattern::regex("^scratch").unwrap()))
+                .then(RuleAction::Float(float_geom))
+                .then(RuleAction::NoFocus),
+            WindowRule::new()
+                .when(Condition::Role(Pattern::exact("popup")))
+                .then(RuleAction::Fullscreen)
+                .then(RuleAction::Hook("mark".into())),
+        ];
+        let marked = Rc::new(Cell::new(None));
+        let m = marked.clone();
+        wm.add_rule_hook("mark", move |_, id| m.set(Some(id)));
+
+        let geom = Geometry::new(0, 0, 100, 100);
+        let (gimp, scratch, popup) = (
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+            wm.conn.add_window(geom),
+        );
+        let props = [
+            (gimp, Atom::WmClass, vec!["gimp".into(), "Gimp".into()]),
+            (scratch, Atom::WmName, vec!["scratchpad".into()]),
+            (popup, Atom::WmWindowRole, vec!["popup".into()]),
+        ];
+        for (id, atom, value) in props {
+            wm.conn
+                .set_property(id, atom.as_ref(), Property::String(value))
+                .unwrap();
+        }
+
+        wm.conn.request_map(gimp);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        let ws = wm.desktop.find("2").unwrap();
+        assert!(ws.contains_window(gimp));
+        assert!(!wm.conn.is_mapped(gimp));
+        assert_eq!(wm.conn.window(gimp).unwrap().border_width, 5);
+
+        wm.conn.request_map(scratch);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        let client = wm.desktop.current().windows.lookup(scratch).unwrap();
+        assert!(client.is_off_layout());
+        assert_eq!(wm.conn.get_geometry(scratch).unwrap(), float_geom);
+        assert_ne!(wm.conn.focused_window(), scratch);
+
+        wm.conn.request_map(popup);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        let client = wm.desktop.current().windows.lookup(popup).unwrap();
+        assert!(client.is_fullscreen());
+        assert_eq!(marked.get(), Some(popup));
+    }
+
+    #[test]
+    fn test_scratchpads() {
+        use super::rules::Pattern;
+        use crate::core::ScratchpadSpec;
+
+        let mut wm = wm();
+        wm.config.scratchpads = vec![
+            ScratchpadSpec::new("term", "true", ["--class", "scratch"], Pattern::exact("scratch")),
+        ];
+
+        // the first toggle spawns the scratchpad
+        wm.toggle_scratchpad("term");
+        assert_eq!(wm.pending_scratchpads, ["term"]);
+        let term = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        wm.conn
+            .set_property(
+                term,
+                Atom::WmClass.as_ref(),
+                Property::String(vec!["scratch".into(), "scratch".into()]),
+            )
+            .unwrap();
+        wm.conn.request_map(term);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        assert!(wm.pending_scratchpads.is_empty());
+        assert_eq!(wm.desktop.scratchpad("term"), Some(term));
+        let client = wm.desktop.current().windows.lookup(term).unwrap();
+        assert!(client.is_off_layout());
+        assert_eq!(wm.conn.get_geometry(term).unwrap(), Geometry::new(910, 490, 100, 100));
+        assert_eq!(wm.conn.focused_window(), term);
+
+        // then hides it outside of every workspace
+        wm.toggle_scratchpad("term");
+        assert!(wm.desktop.is_hidden(term) && wm.desktop.is_managing(term));
+        assert!(wm.desktop.workspaces.iter().all(|ws| !ws.contains_window(term)));
+        assert!(!wm.conn.is_mapped(term));
+
+        // and brings it back to whichever workspace is current
+        wm.goto_workspace("2");
+        wm.toggle_scratchpad("term");
+        assert!(!wm.desktop.is_hidden(term));
+        assert!(wm.desktop.current().contains_window(term) && wm.conn.is_mapped(term));
+    }
+
+    #[test]
+    fn test_supporting_wm_check() {
+        let mut wm = wm();
+        let root = wm.root.id;
+        let check = wm.check_win.unwrap();
+        for id in [root, check] {
+            assert!(matches!(
+                wm.conn.get_property(Atom::NetSupportingWmCheck.as_ref(), id).unwrap(),
+                Some(Property::Window(ids)) if ids == [check]
+            ));
+        }
+        assert!(matches!(
+            wm.conn.get_property(Atom::NetWmName.as_ref(), check).unwrap(),
+            Some(Property::UTF8String(name)) if name == [WM_NAME]
+        ));
+        let Some(Property::Atom(supported)) =
+            wm.conn.get_property(Atom::NetSupported.as_ref(), root).unwrap()
+        else {
+            panic!("_NET_SUPPORTED not set")
+        };
+        assert!(supported.iter().any(|a| a == Atom::NetWmDesktop.as_ref()));
+        assert!(!supported.iter().any(|a| a == Atom::NetSystemTrayS0.as_ref()));
+
+        // quitting cleans up the check window
+        let win = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        wm.conn.request_map(win);
+        wm.quit();
+        assert!(wm.run(Keybinds::new(), Mousebinds::new()).is_ok());
+        assert!(!wm.conn.exists(check));
+    }
+
+    #[test]
+    fn test_desktop_props() {
+        let mut wm = wm();
+        let root = wm.root.id;
+        assert!(matches!(
+            wm.conn.get_property(Atom::NetDesktopNames.as_ref(), root).unwrap(),
+            Some(Property::UTF8String(names)) if names == ["1", "2", "3"]
+        ));
+
+        let win = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        wm.conn.request_map(win);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        assert_eq!(wm.conn.get_wm_desktop(win), Some(0));
+
+        let message = |wm: &ToaruWM<DummyConn>, window, atom: Atom, idx| {
+            let type_ = wm.conn.atom(atom.as_ref()).unwrap();
+            wm.conn.add_event(XEvent::ClientMessage(ClientMessageEvent {
+                window,
+                data: ClientMessageData::U32([idx, 0, 0, 0, 0]),
+                type_,
+            }));
+        };
+
+        message(&wm, win, Atom::NetWmDesktop, 2);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        assert_eq!(wm.conn.get_wm_desktop(win), Some(2));
+
+        // pagers can switch workspaces
+        message(&wm, root, Atom::NetCurrentDesktop, 2);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        assert_eq!(wm.desktop.current_name(), "3");
+        assert!(matches!(
+            wm.conn.get_property(Atom::NetCurrentDesktop.as_ref(), root).unwrap(),
+            Some(Property::Cardinal(2))
+        ));
+    }
+
+    #[test]
+    fn test_urgency() {
+        let mut wm = wm();
+        let geom = Geometry::new(0, 0, 100, 100);
+        let (one, two) = (wm.conn.add_window(geom), wm.conn.add_window(geom));
+        wm.conn.request_map(one);
+        wm.conn.request_map(two);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        wm.send_focused_to("2");
+
+        let type_ = wm.conn.atom(Atom::NetWmState.as_ref()).unwrap();
+        let attention = wm.conn.atom(Atom::NetWmStateDemandsAttention.as_ref()).unwrap();
+        wm.conn.add_event(XEvent::ClientMessage(ClientMessageEvent {
+            window: two,
+            data: ClientMessageData::U32([1, *attention, 0, 0, 0]),
+            type_,
+        }));
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        let urgent = wm.config.border_style(BorderStyle::Urgent);
+        assert_eq!(wm.desktop.find("2").unwrap().urgent_count(), 1);
+        assert_eq!(wm.conn.window(two).unwrap().border_colour, Some(urgent));
+        let states = wm.conn.get_window_states(two).unwrap();
+        assert_eq!(states, [Atom::NetWmStateDemandsAttention.to_string()]);
+
+        // jumping to it clears the urgency
+        wm.focus_urgent();
+        assert_eq!(wm.desktop.current_name(), "2");
+        assert_eq!(wm.conn.focused_window(), two);
+        assert_eq!(wm.desktop.current().urgent_count(), 0);
+        assert!(wm.conn.get_window_states(two).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dock_struts() {
+        let mut wm = wm();
+        let root = wm.root.id;
+        let win = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        let dock = wm.conn.add_window(Geometry::new(0, 0, 30, 1920));
+        let dock_type = Property::Atom(vec![Atom::NetWindowTypeDock.to_string()]);
+        wm.conn.set_property(dock, Atom::NetWmWindowType.as_ref(), dock_type.clone()).unwrap();
+        let strut = vec![0, 0, 30, 0, 0, 0, 0, 0, 0, 1919, 0, 0];
+        wm.conn
+            .set_property(
+                dock,
+                Atom::NetWmStrutPartial.as_ref(),
+                Property::U32List("CARDINAL".into(), strut),
+            )
+            .unwrap();
+        wm.conn.request_map(dock);
+        wm.conn.request_map(win);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        let usable = Geometry::new(0, 30, 1050, 1920);
+        assert!(!wm.desktop.is_managing(dock) && wm.conn.is_mapped(dock));
+        assert_eq!(wm.screens.focused().unwrap().effective_geom(), usable);
+        assert!(wm.conn.get_geometry(win).unwrap().y >= 30);
+        match wm.conn.get_property(Atom::NetWorkarea.as_ref(), root).unwrap() {
+            Some(Property::U32List(_, vals)) => assert_eq!(vals[..4], [0, 30, 1920, 1050]),
+            p => panic!("unexpected property {:?}", p),
+        }
+
+        // a smaller dock on the same edge takes no more space
+        let small = wm.conn.add_window(Geometry::new(0, 0, 20, 1920));
+        wm.conn.set_property(small, Atom::NetWmWindowType.as_ref(), dock_type).unwrap();
+        wm.conn
+            .set_property(
+                small,
+                Atom::NetWmStrut.as_ref(),
+                Property::U32List("CARDINAL".into(), vec![0, 0, 20, 0]),
+            )
+            .unwrap();
+        wm.conn.request_map(small);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        assert_eq!(wm.screens.focused().unwrap().effective_geom(), usable);
+
+        // the space is given back once the docks go away
+        for dock in [dock, small] {
+            wm.conn.unmap_window(dock).unwrap();
+            wm.conn.add_event(XEvent::UnmapNotify(dock, false));
+        }
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+        let screen = wm.screens.focused().unwrap();
+        assert_eq!(screen.effective_geom(), screen.true_geom());
+        assert_eq!(wm.conn.get_geometry(win).unwrap().y, 0);
+    }
+
+    #[test]
+    fn test_screen_reconfigure() {
+        let mut wm = wm();
+        let win = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        wm.conn.request_map(win);
+
+        let changes = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let seen = changes.clone();
+        wm.add_screen_hook(move |_, old, new| seen.borrow_mut().push((old.len(), new.len())));
+
+        // plug in a second monitor
+        wm.conn.set_outputs(vec![
+            Geometry::new(0, 0, 1080, 1920),
+            Geometry::new(1920, 0, 1080, 1920),
+        ]);
+        wm.conn.add_event(XEvent::RandrNotify);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        assert_eq!(wm.screens.len(), 2);
+        assert_eq!(wm.screens.get(0).unwrap().workspaces(), ["1", "2"]);
+        assert_eq!(wm.screens.get(1).unwrap().workspaces(), ["3"]);
+
+        // then unplug the first one, which the other takes the workspaces of
+        wm.conn.remove_output(0);
+        wm.screen_reconfigure().unwrap();
+
+        assert_eq!(wm.screens.len(), 1);
+        let scr = wm.screens.get(0).unwrap();
+        assert_eq!(scr.workspaces(), ["3", "1", "2"]);
+        assert_eq!(scr.active_workspace(), Some("3"));
+        wm.goto_workspace("1");
+        assert!(wm.conn.get_geometry(win).unwrap().x >= 1920);
+
+        // and resize it, which is still the same screen
+        wm.conn.set_outputs(vec![Geometry::new(0, 0, 768, 1366)]);
+        wm.screen_reconfigure().unwrap();
+
+        assert_eq!(wm.screens.get(0).unwrap().workspaces(), ["3", "1", "2"]);
+        let geom = wm.conn.get_geometry(win).unwrap();
+        assert!(geom.width < 1366 && geom.height < 768);
+        assert_eq!(*changes.borrow(), vec![(1, 2), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn test_per_screen_workspaces() {
+        let mut wm = wm();
+        let win = wm.conn.add_window(Geometry::new(0, 0, 100, 100));
+        wm.conn.request_map(win);
+        let _ = wm.run(Keybinds::new(), Mousebinds::new());
+
+        wm.conn.set_outputs(vec![
+            Geometry::new(0, 0, 1080, 1920),
+            Geometry::new(1920, 0, 1080, 1920),
+        ]);
+        wm.screen_reconfigure().unwrap();
+        assert_eq!(wm.screens.get(1).unwrap().active_workspace(), Some("3"));
+
+        // workspace 3 is on the other screen, so we go there instead
+        wm.goto_workspace("3");
+        assert_eq!(wm.screens.focused_idx(), Some(1));
+        assert_eq!(wm.desktop.current_name(), "3");
+        assert_eq!(wm.conn.pointer(), Point { x: 1920 + 960, y: 540 });
+        assert!(wm.conn.is_mapped(win));
+
+        // workspace 2 takes the place of 1 on the first screen
+        wm.goto_workspace("2");
+        assert_eq!(wm.screens.focused_idx(), Some(0));
+        assert_eq!(wm.screens.get(0).unwrap().active_workspace(), Some("2"));
+        assert!(!wm.conn.is_mapped(win));
+
+        wm.move_workspace_to_screen("1", 1);
+        assert_eq!(wm.screens.focused_idx(), Some(1));
+        assert_eq!(wm.screens.get(1).unwrap().workspaces(), ["3", "1"]);
+        assert_eq!(wm.screens.get(1).unwrap().active_workspace(), Some("1"));
+        assert!(wm.conn.is_mapped(win));
+        assert!(wm.conn.get_geometry(win).unwrap().x >= 1920);
+    }
+}
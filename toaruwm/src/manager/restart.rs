@@ -0,0 +1,284 @@
+//! Types for preserving window manager state across in-place restarts.
+//!
+//! Before the window manager re-executes itself, the state of its
+//! `Desktop` is passed to the new process through the environment.
+//! On startup, the new process reads this state back in and uses it
+//! to restore each window to where it was before.
+//!
+//! The state is never written to disk, so other users on the same
+//! machine cannot read or tamper with it.
+//!
+//! The state is stored as plain text with one record per line and
+//! tab-separated fields, so names and layout parameters must not
+//! contain tabs or newlines.
+
+use std::env;
+use std::str::FromStr;
+
+use tracing::{debug, warn};
+
+use crate::core::{Desktop, Ring, Screen};
+use crate::types::Geometry;
+use crate::backend::x::{XWindowID, Xid};
+use crate::{Result, ToaruError};
+
+/// The environment variable used to pass the state to the new process.
+pub(crate) const RESTART_STATE_VAR: &str = "TOARUWM_RESTART_STATE";
+
+/// The saved state of a single client.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SavedClient {
+    pub(crate) id: XWindowID,
+    pub(crate) floating: bool,
+    pub(crate) geom: Geometry,
+}
+
+/// The saved state of a single workspace.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SavedWorkspace {
+    pub(crate) name: String,
+    pub(crate) layout: String,
+    pub(crate) focused: Option<XWindowID>,
+    /// Layout names and their saved parameters.
+    pub(crate) params: Vec<(String, String)>,
+    /// Clients in the order they are stored in the workspace.
+    pub(crate) clients: Vec<SavedClient>,
+}
+
+/// The saved state of a `Desktop`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SavedState {
+    pub(crate) current: String,
+    /// The workspace shown on each screen, by screen index.
+    pub(crate) visible: Vec<(i32, String)>,
+    pub(crate) workspaces: Vec<SavedWorkspace>,
+    /// The names and windows of all scratchpads, hidden or not.
+    pub(crate) scratchpads: Vec<(String, XWindowID)>,
+}
+
+impl SavedState {
+    /// Takes a snapshot of the given `Desktop` and the workspaces
+    /// shown on each screen.
+    pub(crate) fn from_desktop(desktop: &Desktop, screens: &Ring<Screen>) -> Self {
+        Self {
+            current: desktop.current_name().into(),
+            visible: screens
+                .iter()
+                .filter_map(|s| s.active_workspace().map(|ws| (s.idx(), ws.to_string())))
+                .collect(),
+            workspaces: desktop
+                .workspaces
+                .iter()
+                .map(|ws| SavedWorkspace {
+                    name: ws.name.clone(),
+                    layout: ws.layout().into(),
+                    focused: ws.focused_client().map(|c| c.id()),
+                    params: ws
+                        .layouts
+                        .iter()
+                        .filter_map(|l| l.save_params().map(|p| (l.name().to_string(), p)))
+                        .collect(),
+                    clients: ws
+                        .clients()
+                        .map(|c| SavedClient {
+                            id: c.id(),
+                            floating: c.is_off_layout(),
+                            geom: c.geometry(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            scratchpads: desktop
+                .scratchpads()
+                .map(|(name, id)| (name.to_string(), id))
+                .collect(),
+        }
+    }
+
+    /// Reads back a state saved by a previous process, if any.
+    ///
+    /// The state is removed from the environment after it is read.
+    pub(crate) fn take_saved() -> Option<Self> {
+        let state = env::var(RESTART_STATE_VAR);
+        // don't pass this on to any processes we spawn
+        // SAFETY: the window manager runs on a single thread, so nothing
+        // else can be accessing the environment at the same time.
+        unsafe { env::remove_var(RESTART_STATE_VAR) };
+
+        let state = match state {
+            Ok(s) => s,
+            Err(env::VarError::NotPresent) => return None,
+            Err(e) => {
+                warn!("could not read restart state: {}", e);
+                return None;
+            }
+        };
+
+        match state.parse() {
+            Ok(state) => {
+                debug!("Read restart state: {:#?}", state);
+                Some(state)
+            }
+            Err(e) => {
+                warn!("{}", e);
+                None
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SavedState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "current\t{}", self.current)?;
+        for (idx, name) in &self.visible {
+            writeln!(f, "visible\t{}\t{}", idx, name)?;
+        }
+        for ws in &self.workspaces {
+            let focused = ws.focused.map(|id| id.val().to_string());
+            writeln!(
+                f,
+                "workspace\t{}\t{}\t{}",
+                ws.name,
+                ws.layout,
+                focused.as_deref().unwrap_or("-")
+            )?;
+            for (layout, params) in &ws.params {
+                writeln!(f, "params\t{}\t{}", layout, params)?;
+            }
+            for c in &ws.clients {
+                let Geometry { x, y, height, width } = c.geom;
+                writeln!(
+                    f,
+                    "client\t{}\t{}\t{}\t{}\t{}\t{}",
+                    c.id.val(),
+                    c.floating as u8,
+                    x,
+                    y,
+                    height,
+                    width
+                )?;
+            }
+        }
+        for (name, id) in &self.scratchpads {
+            writeln!(f, "scratchpad\t{}\t{}", name, id.val())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SavedState {
+    type Err = ToaruError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let err = |line: &str| ToaruError::Restart(format!("malformed state line: {:?}", line));
+        let num = |field: Option<&str>, line: &str| -> Result<i32> {
+            field.and_then(|f| f.parse().ok()).ok_or_else(|| err(line))
+        };
+
+        let mut state = SavedState::default();
+
+        for line in s.lines().filter(|l| !l.is_empty()) {
+            let mut fields = line.split('\t');
+            match fields.next() {
+                Some("current") => {
+                    state.current = fields.next().ok_or_else(|| err(line))?.into();
+                }
+                Some("visible") => {
+                    let idx = num(fields.next(), line)?;
+                    let name = fields.next().ok_or_else(|| err(line))?;
+                    state.visible.push((idx, name.into()));
+                }
+                Some("workspace") => {
+                    let (Some(name), Some(layout), Some(focused)) =
+                        (fields.next(), fields.next(), fields.next())
+                    else {
+                        return Err(err(line));
+                    };
+                    state.workspaces.push(SavedWorkspace {
+                        name: name.into(),
+                        layout: layout.into(),
+                        focused: focused.parse().ok().map(Xid),
+                        params: Vec::new(),
+                        clients: Vec::new(),
+                    });
+                }
+                Some("params") => {
+                    let (Some(layout), Some(params)) = (fields.next(), fields.next()) else {
+                        return Err(err(line));
+                    };
+                    let ws = state.workspaces.last_mut().ok_or_else(|| err(line))?;
+                    ws.params.push((layout.into(), params.into()));
+                }
+                Some("client") => {
+                    let id = num(fields.next(), line)? as u32;
+                    let floating = num(fields.next(), line)? != 0;
+                    let geom = Geometry::new(
+                        num(fields.next(), line)?,
+                        num(fields.next(), line)?,
+                        num(fields.next(), line)?,
+                        num(fields.next(), line)?,
+                    );
+                    let ws = state.workspaces.last_mut().ok_or_else(|| err(line))?;
+                    ws.clients.push(SavedClient {
+                        id: Xid(id),
+                        floating,
+                        geom,
+                    });
+                }
+                Some("scratchpad") => {
+                    let name = fields.next().ok_or_else(|| err(line))?;
+                    let id = num(fields.next(), line)? as u32;
+                    state.scratchpads.push((name.into(), Xid(id)));
+                }
+                _ => return Err(err(line)),
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_roundtrip() {
+        let state = SavedState {
+            current: "2".into(),
+            visible: vec![(0, "2".into()), (1, "1".into())],
+            workspaces: vec![
+                SavedWorkspace {
+                    name: "1".into(),
+                    layout: "DTiled".into(),
+                    focused: Some(Xid(5)),
+                    params: vec![("DTiled".into(), "0.6 5".into())],
+                    clients: vec![
+                        SavedClient {
+                            id: Xid(5),
+                            floating: false,
+                            geom: Geometry::new(0, 0, 1080, 960),
+                        },
+                        SavedClient {
+                            id: Xid(6),
+                            floating: true,
+                            geom: Geometry::new(-20, 40, 300, 400),
+                        },
+                    ],
+                },
+                SavedWorkspace {
+                    name: "2".into(),
+                    layout: "Floating".into(),
+                    focused: None,
+                    params: vec![],
+                    clients: vec![],
+                },
+            ],
+            scratchpads: vec![("term".into(), Xid(6)), ("music".into(), Xid(7))],
+        };
+
+        let parsed: SavedState = state.to_string().parse().unwrap();
+        assert_eq!(parsed, state);
+        assert!("client\tfoo".parse::<SavedState>().is_err());
+    }
+}
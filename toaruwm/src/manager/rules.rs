@@ -0,0 +1,266 @@
+//! Types for applying per-window policy when windows are mapped.
+//!
+//! A [`WindowRule`] pairs a set of [`Condition`]s with a set of
+//! [`RuleAction`]s. When a window is mapped, every rule whose
+//! conditions all match the window has its actions applied, in
+//! the order the rules were given. Where two actions conflict,
+//! such as two rules sending the window to different workspaces,
+//! the one applied last wins.
+//!
+//! # Example
+//!
+//! ```rust
+//! use toaruwm::manager::rules::{Condition, Pattern, RuleAction, WindowRule};
+//! use toaruwm::ToaruConfig;
+//!
+//! let config = ToaruConfig::builder()
+//!     .rules([
+//!         WindowRule::new()
+//!             .when(Condition::Class(Pattern::exact("Firefox")))
+//!             .then(RuleAction::Workspace("2".into())),
+//!         WindowRule::new()
+//!             .when(Condition::Title(Pattern::regex("^Picture-in-Picture$").unwrap()))
+//!             .then(RuleAction::SkipLayout)
+//!             .then(RuleAction::NoFocus),
+//!     ])
+//!     .finish(|_| Ok(()))
+//!     .unwrap();
+//! ```
+
+use regex::Regex;
+
+use crate::backend::x::{Atom, XConn, XWindowID};
+use crate::core::Client;
+use crate::types::Geometry;
+use crate::{Result, ToaruError};
+
+/// A way of matching a string property of a window.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// The property must be exactly equal to the string.
+    Exact(String),
+    /// The property must contain a match for the regex.
+    Regex(Regex),
+}
+
+impl Pattern {
+    /// Creates a pattern matching the string exactly.
+    pub fn exact<S: Into<String>>(s: S) -> Self {
+        Self::Exact(s.into())
+    }
+
+    /// Creates a pattern from a regular expression.
+    ///
+    /// Returns an error if the expression is invalid.
+    pub fn regex(re: &str) -> Result<Self> {
+        Regex::new(re)
+            .map(Self::Regex)
+            .map_err(|e| ToaruError::InvalidConfig(format!("invalid rule pattern: {}", e)))
+    }
+
+    /// Checks whether the pattern matches the given string.
+    pub fn matches(&self, s: &str) -> bool {
+        match self {
+            Self::Exact(exact) => exact == s,
+            Self::Regex(re) => re.is_match(s),
+        }
+    }
+}
+
+/// A property that a window must have for a rule to apply to it.
+#[derive(Debug, Clone)]
+pub enum Condition {
+    /// The class part of `WM_CLASS`.
+    Class(Pattern),
+    /// The instance part of `WM_CLASS`.
+    Instance(Pattern),
+    /// `_NET_WM_NAME`, or `WM_NAME` if it is not set.
+    Title(Pattern),
+    /// `WM_WINDOW_ROLE`.
+    Role(Pattern),
+    /// One of the window's `_NET_WM_WINDOW_TYPE`s.
+    WindowType(Atom),
+    /// Whether the window sets `WM_TRANSIENT_FOR`.
+    Transient(bool),
+}
+
+impl Condition {
+    /// Checks whether the window described by `props` meets the condition.
+    pub fn matches(&self, props: &WindowProps) -> bool {
+        match self {
+            Self::Class(p) => p.matches(&props.class),
+            Self::Instance(p) => p.matches(&props.instance),
+            Self::Title(p) => p.matches(&props.title),
+            Self::Role(p) => props.role.as_deref().is_some_and(|r| p.matches(r)),
+            Self::WindowType(ty) => props.types.iter().any(|t| t == ty.as_ref()),
+            Self::Transient(transient) => props.transient == *transient,
+        }
+    }
+}
+
+/// Something done to a window that a rule applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleAction {
+    /// Place the window on the named workspace.
+    Workspace(String),
+    /// Float the window with the given geometry.
+    Float(Geometry),
+    /// Make the window fullscreen.
+    Fullscreen,
+    /// Give the window a border of the given width,
+    /// instead of the configured one.
+    BorderPx(u32),
+    /// Never give the window the input focus.
+    NoFocus,
+    /// Keep the window off the layout, at its own geometry.
+    SkipLayout,
+    /// Run the named rule hook on the window.
+    ///
+    /// Rule hooks are added with
+    /// [`WindowManager::add_rule_hook`](super::WindowManager::add_rule_hook).
+    Hook(String),
+}
+
+/// A set of conditions, and the actions to apply to windows
+/// that meet all of them.
+///
+/// A rule without any conditions applies to every window.
+#[derive(Debug, Clone, Default)]
+pub struct WindowRule {
+    conditions: Vec<Condition>,
+    actions: Vec<RuleAction>,
+}
+
+impl WindowRule {
+    /// Creates a new rule with no conditions or actions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a condition that windows must meet.
+    pub fn when(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Adds an action to apply to windows meeting the conditions.
+    pub fn then(mut self, action: RuleAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// The conditions of the rule.
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    /// The actions of the rule.
+    pub fn actions(&self) -> &[RuleAction] {
+        &self.actions
+    }
+
+    /// Checks whether the window described by `props` meets
+    /// every condition of the rule.
+    pub fn matches(&self, props: &WindowProps) -> bool {
+        self.conditions.iter().all(|c| c.matches(props))
+    }
+}
+
+/// The properties of a window that rules are matched against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowProps {
+    /// The class part of `WM_CLASS`.
+    pub class: String,
+    /// The instance part of `WM_CLASS`.
+    pub instance: String,
+    /// `_NET_WM_NAME`, or `WM_NAME` if it is not set.
+    pub title: String,
+    /// `WM_WINDOW_ROLE`, if set.
+    pub role: Option<String>,
+    /// The window's `_NET_WM_WINDOW_TYPE`s.
+    pub types: Vec<String>,
+    /// Whether the window sets `WM_TRANSIENT_FOR`.
+    pub transient: bool,
+}
+
+impl WindowProps {
+    /// Reads the properties of a window from the X server.
+    pub fn query<X: XConn>(conn: &X, window: XWindowID) -> Self {
+        let (instance, class) = conn.get_wm_class(window);
+        Self {
+            class,
+            instance,
+            title: conn.get_wm_name(window),
+            role: conn.get_wm_window_role(window),
+            types: conn.get_window_type(window).unwrap_or_default(),
+            transient: conn.get_wm_transient_for(window).is_some(),
+        }
+    }
+}
+
+/// Returns the actions of every rule matching `props`, in order.
+pub(crate) fn matching_actions(rules: &[WindowRule], props: &WindowProps) -> Vec<RuleAction> {
+    rules
+        .iter()
+        .filter(|r| r.matches(props))
+        .flat_map(|r| r.actions().iter().cloned())
+        .collect()
+}
+
+/// Applies the actions among `actions` that change the client itself
+/// rather than where it is placed, i.e. `BorderPx` and `NoFocus`.
+///
+/// Clients rebuilt after a restart need these applied again.
+pub(crate) fn apply_to_client(client: &mut Client, actions: &[RuleAction]) {
+    for action in actions {
+        match action {
+            RuleAction::BorderPx(px) => client.set_border_px(Some(*px)),
+            RuleAction::NoFocus => client.set_never_focus(true),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_matching() {
+        let props = WindowProps {
+            class: "Firefox".into(),
+            instance: "Navigator".into(),
+            title: "Picture-in-Picture".into(),
+            role: Some("PictureInPicture".into()),
+            types: vec![Atom::NetWindowTypeNormal.to_string()],
+            transient: false,
+        };
+        let rules = [
+            WindowRule::new()
+                .when(Condition::Class(Pattern::exact("Firefox")))
+                .then(RuleAction::Workspace("2".into())),
+            WindowRule::new()
+                .when(Condition::Title(Pattern::regex("^Picture-in").unwrap()))
+                .when(Condition::Role(Pattern::exact("PictureInPicture")))
+                .then(RuleAction::SkipLayout),
+            WindowRule::new()
+                .when(Condition::Instance(Pattern::exact("Navigator")))
+                .when(Condition::Transient(true))
+                .then(RuleAction::NoFocus),
+            WindowRule::new()
+                .when(Condition::WindowType(Atom::NetWindowTypeNormal))
+                .then(RuleAction::BorderPx(0)),
+        ];
+
+        assert_eq!(
+            matching_actions(&rules, &props),
+            [
+                RuleAction::Workspace("2".into()),
+                RuleAction::SkipLayout,
+                RuleAction::BorderPx(0),
+            ]
+        );
+        assert!(Pattern::regex("(").is_err());
+        assert!(!Condition::Role(Pattern::exact("")).matches(&WindowProps::default()));
+    }
+}
@@ -7,14 +7,16 @@
 
 use std::any::Any;
 use std::collections::HashMap;
+use std::time::Duration;
 
 use custom_debug_derive::Debug;
 
 use crate::core::{
     types::{BorderStyle, Color},
-    Client, Desktop, Ring, Workspace,
+    Client, Desktop, Ring, ScratchpadSpec, Workspace,
 };
 use crate::backend::x::{XConn, XWindow, XWindowID};
+use crate::manager::rules::WindowRule;
 
 /// An object that can provide information about window manager
 /// configuration at runtime.
@@ -65,6 +67,20 @@ pub trait RuntimeConfig {
     /// Return information about the floating classes.
     fn float_classes(&self) -> &[String];
 
+    /// Return the rules applied to windows as they are mapped.
+    ///
+    /// By default, there are no rules.
+    fn rules(&self) -> &[WindowRule] {
+        &[]
+    }
+
+    /// Return the named scratchpads that can be toggled.
+    ///
+    /// By default, there are no scratchpads.
+    fn scratchpads(&self) -> &[ScratchpadSpec] {
+        &[]
+    }
+
     /// Return information about the window border thickness.
     fn border_px(&self) -> u32;
 
@@ -77,6 +93,21 @@ pub trait RuntimeConfig {
     /// Return whether the focus should follow the pointer.
     fn focus_follows_ptr(&self) -> bool;
 
+    /// Return how clients asking to be activated should be handled.
+    ///
+    /// This defaults to [`ActivationPolicy::default`].
+    fn activation_policy(&self) -> ActivationPolicy {
+        ActivationPolicy::default()
+    }
+
+    /// Return how long a client asked to close is given to do so
+    /// before it is killed.
+    ///
+    /// This defaults to 5 seconds.
+    fn close_timeout(&self) -> Duration {
+        Duration::from_secs(5)
+    }
+
     /// Retrieve arbitrary key value pairs from storage.
     ///
     /// Should return None if the key does not exist in
@@ -101,6 +132,21 @@ pub trait RuntimeConfig {
     }
 }
 
+/// How the window manager responds to a client asking to be activated
+/// through `_NET_ACTIVE_WINDOW`.
+///
+/// Requests sent by pagers and taskbars on behalf of the user
+/// are always honoured; this only applies to requests sent by
+/// applications themselves.
+#[derive(std::fmt::Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ActivationPolicy {
+    /// Switch to the client's workspace and focus it.
+    Focus,
+    /// Leave the focus where it is and mark the client as urgent.
+    #[default]
+    MarkUrgent,
+}
+
 /// The runtime configuration of the
 /// [`WindowManager`](super::WindowManager).
 ///
@@ -119,9 +165,13 @@ pub trait RuntimeConfig {
 #[derive(Debug)]
 pub struct WmConfig {
     pub(crate) float_classes: Vec<String>,
+    pub(crate) rules: Vec<WindowRule>,
+    pub(crate) scratchpads: Vec<ScratchpadSpec>,
     pub(crate) border_px: u32,
     pub(crate) window_gap: u32,
     pub(crate) focus_follows_ptr: bool,
+    pub(crate) activation_policy: ActivationPolicy,
+    pub(crate) close_timeout: Duration,
     pub(crate) unfocused: Color,
     pub(crate) focused: Color,
     pub(crate) urgent: Color,
@@ -133,6 +183,14 @@ impl RuntimeConfig for WmConfig {
         &self.float_classes
     }
 
+    fn rules(&self) -> &[WindowRule] {
+        &self.rules
+    }
+
+    fn scratchpads(&self) -> &[ScratchpadSpec] {
+        &self.scratchpads
+    }
+
     fn border_px(&self) -> u32 {
         self.border_px
     }
@@ -152,6 +210,14 @@ impl RuntimeConfig for WmConfig {
         self.focus_follows_ptr
     }
 
+    fn activation_policy(&self) -> ActivationPolicy {
+        self.activation_policy
+    }
+
+    fn close_timeout(&self) -> Duration {
+        self.close_timeout
+    }
+
     fn get_key(&self, key: &str) -> Option<&dyn Any> {
         self.keys.get(&key.to_string()).map(|v| v as &dyn Any)
     }